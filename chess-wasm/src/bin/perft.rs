@@ -0,0 +1,189 @@
+//! Count the leaf nodes below each legal move, and compare them with a UCI engine.
+//!
+//!     perft [--engine <program>] [--chess960] [--hash <mb>] <depth> [<fen>]
+//!
+//! Without an engine the counts are printed like Stockfish's `go perft`, and `--hash` caches
+//! the counts of the positions already seen in a transposition table of that size. With one,
+//! the counts are printed side by side and the first mismatch is followed down to the position
+//! where the legal moves differ. The exit code is 1 if a mismatch was found.
+
+use chess_wasm::chess::Chess;
+use chess_wasm::perft::{compare, Mismatch, Perft, UciEngine};
+use chess_wasm::pgn::DEFAULT_POSITION;
+use chess_wasm::transposition::TranspositionTable;
+use std::collections::BTreeSet;
+use std::process::exit;
+
+const USAGE: &str = "usage: perft [--engine <program>] [--chess960] [--hash <mb>] <depth> [<fen>]";
+
+struct Args {
+    engine: Option<String>,
+    chess960: bool,
+    /// the size of the transposition table in MB
+    hash: Option<usize>,
+    depth: u8,
+    fen: String,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut engine = None;
+    let mut chess960 = false;
+    let mut hash = None;
+    let mut positional = vec![];
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--engine" => engine = Some(args.next().ok_or("--engine needs a program")?),
+            "--chess960" => chess960 = true,
+            "--hash" => {
+                let mb = args.next().ok_or("--hash needs a size in MB")?;
+                hash = Some(
+                    mb.parse()
+                        .map_err(|_| format!("invalid hash size '{}'", mb))?,
+                );
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => positional.push(arg),
+        }
+    }
+
+    if positional.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let depth = positional[0]
+        .parse()
+        .map_err(|_| format!("invalid depth '{}'", positional[0]))?;
+
+    // the FEN can be a single argument or split on its spaces
+    let fen = if positional.len() > 1 {
+        positional[1..].join(" ")
+    } else {
+        DEFAULT_POSITION.to_string()
+    };
+
+    Ok(Args {
+        engine,
+        chess960,
+        hash,
+        depth,
+        fen,
+    })
+}
+
+/// Print the counts of each move side by side, marking those that differ
+fn print_counts(chess: &Perft, engine: &Perft) {
+    let moves: BTreeSet<&String> = chess
+        .child_count()
+        .keys()
+        .chain(engine.child_count().keys())
+        .collect();
+
+    let count = |perft: &Perft, uci: &String| match perft.child_count().get(uci) {
+        Some(count) => count.to_string(),
+        None => "-".to_string(),
+    };
+
+    println!("{:<7} {:>12} {:>12}", "move", "chess", "engine");
+
+    for uci in moves {
+        let (ours, theirs) = (count(chess, uci), count(engine, uci));
+        let marker = if ours == theirs { "" } else { "  <--" };
+
+        println!("{:<7} {:>12} {:>12}{}", uci, ours, theirs, marker);
+    }
+
+    println!(
+        "{:<7} {:>12} {:>12}",
+        "total",
+        chess.total_count(),
+        engine.total_count()
+    );
+}
+
+fn print_mismatch(mismatch: &Mismatch) {
+    println!();
+    println!("first mismatch at depth {}", mismatch.depth);
+    println!("fen: {}", mismatch.fen);
+
+    if !mismatch.moves.is_empty() {
+        println!("moves: {}", mismatch.moves.join(" "));
+    }
+
+    let missing = mismatch.missing_moves();
+    let extra = mismatch.extra_moves();
+
+    if !missing.is_empty() {
+        println!("missing moves: {}", missing.join(" "));
+    }
+
+    if !extra.is_empty() {
+        println!("illegal moves: {}", extra.join(" "));
+    }
+
+    if !mismatch.moves.is_empty() {
+        println!();
+        print_counts(&mismatch.chess, &mismatch.engine);
+    }
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        exit(2);
+    });
+
+    let mut chess = Chess::new();
+    chess.set_chess960(args.chess960);
+
+    if let Err(err) = chess.try_load_fen(&args.fen) {
+        eprintln!("invalid FEN: {}", err);
+        exit(2);
+    }
+
+    let program = match args.engine {
+        Some(program) => program,
+        None => {
+            let perft = match args.hash {
+                Some(mb) => {
+                    let mut table = TranspositionTable::new(mb);
+                    Perft::divide_with_table(&mut chess, args.depth, &mut table)
+                }
+                None => Perft::divide(&mut chess, args.depth),
+            };
+
+            for (uci, count) in perft.child_count() {
+                println!("{}: {}", uci, count);
+            }
+
+            println!();
+            println!("Nodes searched: {}", perft.total_count());
+            return;
+        }
+    };
+
+    let mut engine = UciEngine::new(&program, &[]).unwrap_or_else(|err| {
+        eprintln!("can't start {}: {}", program, err);
+        exit(2);
+    });
+
+    if chess.is_chess960() {
+        if let Err(err) = engine.set_option("UCI_Chess960", "true") {
+            eprintln!("{}: {}", program, err);
+            exit(2);
+        }
+    }
+
+    let comparison = compare(&mut chess, args.depth, &mut engine).unwrap_or_else(|err| {
+        eprintln!("{}: {}", program, err);
+        exit(2);
+    });
+
+    print_counts(&comparison.chess, &comparison.engine);
+
+    if let Some(mismatch) = comparison.mismatch {
+        print_mismatch(&mismatch);
+        exit(1);
+    }
+}
@@ -0,0 +1,384 @@
+//! A chess engine speaking the Universal Chess Interface over stdin and stdout, for GUIs like
+//! Cute Chess or Arena.
+//!
+//! The search runs on its own thread, so `stop` and `isready` are answered while it thinks,
+//! the other commands wait for it to finish. At the end of the input a search is finished
+//! before exiting, unless it is infinite, so commands can be piped from a file:
+//!
+//!     printf 'position startpos moves e2e4\ngo depth 5\n' | uci
+
+use chess_wasm::chess::{Chess, Move};
+use chess_wasm::pgn::DEFAULT_POSITION;
+use chess_wasm::search::{Search, SearchLimits, SearchResult};
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const NAME: &str = concat!("chess-wasm ", env!("CARGO_PKG_VERSION"));
+
+const MAX_HASH_MB: usize = 1024;
+const MAX_MULTI_PV: usize = 64;
+
+/// The time kept aside for the GUI to receive the move
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+
+/// The number of moves left to play when the GUI doesn't say
+const MOVES_TO_GO: u32 = 30;
+
+/// What the search thread takes while it runs
+struct State {
+    chess: Chess,
+    search: Search,
+}
+
+struct Engine {
+    /// `None` while a search is running
+    state: Option<State>,
+    thread: Option<JoinHandle<State>>,
+    stop: Arc<AtomicBool>,
+    /// whether the running search only ends with `stop`
+    infinite: bool,
+    chess960: bool,
+}
+
+impl Engine {
+    fn new() -> Engine {
+        let search = Search::new(SearchLimits::default());
+        let stop = search.stop_flag();
+
+        Engine {
+            state: Some(State {
+                chess: start_position(false),
+                search,
+            }),
+            thread: None,
+            stop,
+            infinite: false,
+            chess960: false,
+        }
+    }
+
+    /// Handle a command, returns false on `quit`
+    fn handle(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        let (command, args) = match tokens.split_first() {
+            Some((command, args)) => (*command, args),
+            None => return true,
+        };
+
+        let result = match command {
+            "uci" => {
+                println!("id name {}", NAME);
+                println!("id author {}", env!("CARGO_PKG_AUTHORS"));
+                println!(
+                    "option name Hash type spin default {} min 1 max {}",
+                    chess_wasm::transposition::DEFAULT_SIZE_MB,
+                    MAX_HASH_MB
+                );
+                println!("option name Threads type spin default 1 min 1 max 1");
+                println!(
+                    "option name MultiPV type spin default 1 min 1 max {}",
+                    MAX_MULTI_PV
+                );
+                println!("option name UCI_Chess960 type check default false");
+                println!("uciok");
+                Ok(())
+            }
+            "isready" => {
+                println!("readyok");
+                Ok(())
+            }
+            "ucinewgame" => {
+                let chess960 = self.chess960;
+                let state = self.state();
+
+                state.chess = start_position(chess960);
+                state.search.table_mut().clear();
+                Ok(())
+            }
+            "setoption" => self.set_option(args),
+            "position" => self.position(args),
+            "go" => self.go(args),
+            "stop" => {
+                self.stop.store(true, Ordering::SeqCst);
+                self.wait();
+                Ok(())
+            }
+            "quit" => {
+                self.stop.store(true, Ordering::SeqCst);
+                self.wait();
+                return false;
+            }
+            // pondering isn't supported, and there is nothing to debug or register
+            "debug" | "register" | "ponderhit" => Ok(()),
+            _ => Err(format!("unknown command '{}'", line.trim())),
+        };
+
+        if let Err(message) = result {
+            println!("info string {}", message);
+        }
+
+        true
+    }
+
+    /// Wait for the search to finish and take back the state it had
+    fn wait(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.state = Some(thread.join().expect("the search thread panicked"));
+        }
+    }
+
+    fn state(&mut self) -> &mut State {
+        self.wait();
+        self.state
+            .as_mut()
+            .expect("the state is back once the search is over")
+    }
+
+    /// `setoption name <name> value <value>`
+    fn set_option(&mut self, args: &[&str]) -> Result<(), String> {
+        let value_index = args.iter().position(|&arg| arg == "value");
+        let name = args[..value_index.unwrap_or(args.len())]
+            .iter()
+            .skip_while(|&&arg| arg == "name")
+            .copied()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        let value = value_index.map_or(String::new(), |index| args[index + 1..].join(" "));
+
+        let number = || {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("invalid value '{}' for {}", value, name))
+        };
+
+        match name.to_ascii_lowercase().as_str() {
+            "hash" => {
+                let mb = number()?.clamp(1, MAX_HASH_MB);
+                self.state().search.table_mut().resize(mb);
+            }
+            "multipv" => {
+                let lines = number()?.clamp(1, MAX_MULTI_PV);
+                self.state().search.set_multi_pv(lines);
+            }
+            // the search runs on a single thread
+            "threads" => {
+                number()?;
+            }
+            "uci_chess960" => {
+                self.chess960 = value == "true";
+
+                let chess960 = self.chess960;
+                self.state().chess.set_chess960(chess960);
+            }
+            _ => return Err(format!("unknown option '{}'", name)),
+        }
+
+        Ok(())
+    }
+
+    /// `position startpos|fen <fen> [moves <move>...]`. The moves are played until one is
+    /// illegal.
+    fn position(&mut self, args: &[&str]) -> Result<(), String> {
+        let (fen, rest) = match args.split_first() {
+            Some((&"startpos", rest)) => (DEFAULT_POSITION.to_string(), rest),
+            Some((&"fen", rest)) => {
+                let end = rest
+                    .iter()
+                    .position(|&arg| arg == "moves")
+                    .unwrap_or(rest.len());
+
+                (rest[..end].join(" "), &rest[end..])
+            }
+            _ => return Err("position needs startpos or fen".to_string()),
+        };
+
+        let mut chess = Chess::new();
+        chess.set_chess960(self.chess960);
+        chess
+            .try_load_fen(&fen)
+            .map_err(|err| format!("invalid FEN '{}': {}", fen, err))?;
+
+        let moves = match rest.split_first() {
+            Some((&"moves", moves)) => moves,
+            _ => &[],
+        };
+
+        let mut result = Ok(());
+
+        for uci in moves {
+            if let Err(err) = chess.make_uci_move(uci) {
+                result = Err(format!("illegal move '{}': {}", uci, err));
+                break;
+            }
+        }
+
+        self.state().chess = chess;
+
+        result
+    }
+
+    /// `go [depth <plies>] [nodes <n>] [movetime <ms>] [wtime <ms>] [btime <ms>]
+    /// [winc <ms>] [binc <ms>] [movestogo <n>] [infinite]`
+    fn go(&mut self, args: &[&str]) -> Result<(), String> {
+        let mut limits = SearchLimits::default();
+        let mut infinite = false;
+        let (mut wtime, mut btime, mut winc, mut binc) = (None, None, 0, 0);
+        let mut moves_to_go = MOVES_TO_GO;
+        let mut args = args.iter();
+
+        while let Some(&arg) = args.next() {
+            let mut number = || -> Result<u64, String> {
+                let value = args.next().ok_or(format!("go {} needs a value", arg))?;
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid value '{}' for go {}", value, arg))
+            };
+
+            match arg {
+                "depth" => limits.depth = Some(number()?.min(u8::MAX as u64) as u8),
+                "nodes" => limits.nodes = Some(number()?),
+                "movetime" => limits.movetime = Some(Duration::from_millis(number()?)),
+                "wtime" => wtime = Some(number()?),
+                "btime" => btime = Some(number()?),
+                "winc" => winc = number()?,
+                "binc" => binc = number()?,
+                "movestogo" => moves_to_go = number()?.max(1) as u32,
+                "infinite" => infinite = true,
+                _ => {}
+            }
+        }
+
+        let state = self.state();
+
+        let (time, increment) = match state.chess.turn() {
+            chess_wasm::chess2::Color::White => (wtime, winc),
+            chess_wasm::chess2::Color::Black => (btime, binc),
+        };
+
+        if let Some(time) = time {
+            let allotted = allotted_time(
+                Duration::from_millis(time),
+                Duration::from_millis(increment),
+                moves_to_go,
+            );
+
+            limits.movetime = Some(
+                limits
+                    .movetime
+                    .map_or(allotted, |movetime| movetime.min(allotted)),
+            );
+        }
+
+        // without any limit the search goes on until `stop`
+        if limits == SearchLimits::default() {
+            infinite = true;
+        }
+
+        if infinite {
+            limits = SearchLimits::default();
+        }
+
+        let mut state = self.state.take().expect("the search is over");
+        state.search.set_limits(limits);
+
+        let stop = self.stop.clone();
+        stop.store(false, Ordering::SeqCst);
+        self.infinite = infinite;
+
+        self.thread = Some(thread::spawn(move || {
+            let result = state
+                .search
+                .run_with(&mut state.chess, |line| println!("{}", info(line)));
+
+            // the best move of an infinite search is only sent after `stop`, even if there is
+            // nothing left to search
+            while infinite && !stop.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(5));
+            }
+
+            println!("{}", best_move(&result));
+
+            state
+        }));
+
+        Ok(())
+    }
+}
+
+fn start_position(chess960: bool) -> Chess {
+    let mut chess = Chess::new();
+    chess.set_chess960(chess960);
+    chess.load_fen(DEFAULT_POSITION.to_string());
+
+    chess
+}
+
+/// An even share of the time left for the moves to go, and most of the increment, keeping
+/// some time aside so the flag never falls
+fn allotted_time(time: Duration, increment: Duration, moves_to_go: u32) -> Duration {
+    let available = time.saturating_sub(MOVE_OVERHEAD);
+    let share = time / moves_to_go + increment * 3 / 4;
+
+    share.min(available).max(Duration::from_millis(1))
+}
+
+fn info(line: &SearchResult) -> String {
+    let score = match line.mate_in() {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", line.score),
+    };
+
+    let millis = line.time.as_millis() as u64;
+    let nps = line.nodes * 1000 / millis.max(1);
+
+    let mut info = format!(
+        "info depth {} multipv {} score {} nodes {} nps {} hashfull {} time {}",
+        line.depth, line.multipv, score, line.nodes, nps, line.hashfull, millis
+    );
+
+    if !line.pv.is_empty() {
+        let pv: Vec<String> = line.pv.iter().map(Move::to_uci).collect();
+
+        info.push_str(" pv ");
+        info.push_str(&pv.join(" "));
+    }
+
+    info
+}
+
+/// `bestmove <move> [ponder <move>]`, or the null move `0000` if there is no legal move
+fn best_move(result: &SearchResult) -> String {
+    match (result.pv.first(), result.pv.get(1)) {
+        (Some(best), Some(ponder)) => {
+            format!("bestmove {} ponder {}", best.to_uci(), ponder.to_uci())
+        }
+        (Some(best), None) => format!("bestmove {}", best.to_uci()),
+        _ => "bestmove 0000".to_string(),
+    }
+}
+
+fn main() {
+    let mut engine = Engine::new();
+
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        if !engine.handle(&line) {
+            return;
+        }
+    }
+
+    // the input is over, an infinite search would never get its `stop`
+    if engine.infinite {
+        engine.stop.store(true, Ordering::SeqCst);
+    }
+
+    engine.wait();
+}
@@ -1,7 +1,10 @@
 use crate::chess::Piece::*;
+use crate::chess2::board::{bit, Bitboard};
+use crate::chess2::position::{self, castling_right};
+use crate::chess2::{self, square, zobrist, Color, Position, Role, Square};
+use crate::chess960;
 use crate::errors::*;
-use regex::Regex;
-use std::collections::HashMap;
+use crate::outcome::{Outcome, Termination};
 
 const BOARD_SIZE: u8 = 128;
 const COLOR_MASK: u8 = 128; // 10000000
@@ -51,78 +54,7 @@ const BISHOP_DELTAS: [i8; 4] = [17, 15, -17, -15];
 const ROOK_DELTAS: [i8; 4] = [16, -16, 1, -1];
 const QUEEN_DELTAS: [i8; 8] = [16, -16, 1, -1, 17, 15, -17, -15];
 const KNIGHT_DELTAS: [i8; 8] = [14, 31, 18, 33, -14, -31, -18, -33];
-const KING_DELTAS: [i8; 10] = [1, 16, 17, 15, -1, -16, -17, -15, 2, -2];
-const MOVED_KING_DELTAS: [i8; 8] = [1, 16, 17, 15, -1, -16, -17, -15];
-
-// why does it have 239 items?
-// because of how the indexes of the squares on the real board are laid out due to the fact that
-// we have to use some indexes in between to represent the dummy board
-/*
-    q & b = 40
-    q & r = 34
-    q & b & k = 56
-
-    queen, bishop, king = 56
-
-    queen, bishop, white pawn, king = 57  = 00111001
-    queen, bishop, black pawn, king = 185 = 10111001
-
-    00100000
-    00001000
-    10000001
-    00010000
-
-
-
-    queen, bishop can move diagonally to any square = 40 = 00101000
-
-    knight can move in L shape = 4 = 00000100
-
-    queen, rook can move vertically or horizontally to any square = 34 = 00100010
-
-    queen, rook, king = 50 = 00110010
-
-  what is the signifance of the fact that the diff. between each square is unique?
-  because they are unique, we can store every single diff. (offset by 119) in an array for lookup.
-  This way, we can quickly check if a square can be attacked by just finding the difference between the indexes
-*/
-#[rustfmt::skip]
-const ATTACKS: [u8; 239]= [
-   40, 0, 0, 0, 0, 0, 0, 34,  0, 0, 0, 0, 0, 0,40, 0, // Notice how the non-zero numbers are placed very specifically,
-   0, 40, 0, 0, 0, 0, 0, 34,  0, 0, 0, 0, 0,40, 0, 0, 
-   0, 0, 40, 0, 0, 0, 0, 34,  0, 0, 0, 0,40, 0, 0, 0,
-   0, 0, 0, 40, 0, 0, 0, 34,  0, 0, 0,40, 0, 0, 0, 0,
-   0, 0, 0, 0, 40, 0, 0, 34,  0, 0,40, 0, 0, 0, 0, 0,
-   0, 0, 0, 0, 0, 40, 4, 34,  4,40, 0, 0, 0, 0, 0, 0,
-   0, 0, 0, 0, 0, 4, 57, 50, 57, 4, 0, 0, 0, 0, 0, 0,
-   34,34,34,34,34,34,50,  0, 50,34,34,34,34,34,34, 0, // Note the zero in the very middle, it basically represents the current piece that is being evaluated for attacks
-   0, 0, 0, 0, 0, 4,185, 50,185, 4, 0, 0, 0, 0, 0, 0, // But the piece isn't always in the middle? We can "move" it to the middle by adding 119
-   0, 0, 0, 0, 0, 40, 4, 34, 4, 40, 0, 0, 0, 0, 0, 0, // and then applying the difference between two squares to find the index relative to the piece in the middle
-   0, 0, 0, 0, 40, 0, 0, 34, 0, 0, 40, 0, 0, 0, 0, 0,
-   0, 0, 0, 40, 0, 0, 0, 34, 0, 0, 0, 40, 0, 0, 0, 0,
-   0, 0, 40, 0, 0, 0, 0, 34, 0, 0, 0, 0, 40, 0, 0, 0,
-   0, 40, 0, 0, 0, 0, 0, 34, 0, 0, 0, 0, 0, 40, 0, 0,
-   40, 0, 0, 0, 0, 0, 0, 34,  0, 0, 0, 0, 0, 0, 40, 
-];
-
-#[rustfmt::skip]
-const DELTAS: [i8; 239]= [
-   -17,  0,  0,  0,  0,  0,  0,-16,  0,  0,  0,  0,  0,  0,-15, 0,
-     0,-17,  0,  0,  0,  0,  0,-16,  0,  0,  0,  0,  0,-15,  0, 0,
-     0,  0,-17,  0,  0,  0,  0,-16,  0,  0,  0,  0,-15,  0,  0, 0,
-     0,  0,  0,-17,  0,  0,  0,-16,  0,  0,  0,-15,  0,  0,  0, 0,
-     0,  0,  0,  0,-17,  0,  0,-16,  0,  0,-15,  0,  0,  0,  0, 0,
-     0,  0,  0,  0,  0,-17,  0,-16,  0,-15,  0,  0,  0,  0,  0, 0,
-     0,  0,  0,  0,  0,  0,-17,-16,-15,  0,  0,  0,  0,  0,  0, 0,
-    -1, -1, -1, -1, -1, -1, -1,  0,  1,  1,  1,  1,  1,  1,  1, 0,
-     0,  0,  0,  0,  0,  0, 15, 16, 17,  0,  0,  0,  0,  0,  0, 0,
-     0,  0,  0,  0,  0, 15,  0, 16,  0, 17,  0,  0,  0,  0,  0, 0,
-     0,  0,  0,  0, 15,  0,  0, 16,  0,  0, 17,  0,  0,  0,  0, 0,
-     0,  0,  0, 15,  0,  0,  0, 16,  0,  0,  0, 17,  0,  0,  0, 0,
-     0,  0, 15,  0,  0,  0,  0, 16,  0,  0,  0,  0, 17,  0,  0, 0,
-     0, 15,  0,  0,  0,  0,  0, 16,  0,  0,  0,  0,  0, 17,  0, 0,
-    15,  0,  0,  0,  0,  0,  0, 16,  0,  0,  0,  0,  0,  0, 17
- ];
+const KING_DELTAS: [i8; 8] = [1, 16, 17, 15, -1, -16, -17, -15];
 
 const BOARD_MAP: [u8; 64] = [
     0, 1, 2, 3, 4, 5, 6, 7, 16, 17, 18, 19, 20, 21, 22, 23, 32, 33, 34, 35, 36, 37, 38, 39, 48, 49,
@@ -132,11 +64,42 @@ const BOARD_MAP: [u8; 64] = [
 
 const FILES: [&str; 8] = ["a", "b", "c", "d", "e", "f", "g", "h"];
 
-fn is_number(arg: &str) -> bool {
-    arg.chars()
-        .collect::<Vec<char>>()
-        .iter()
-        .all(|&c| c.is_digit(10))
+/// Convert a square like "e4" to its index on the board, or `None` if it isn't a valid square
+fn parse_square(notation: &str) -> Option<PieceIndex> {
+    let mut parts = notation.chars();
+
+    let file = parts.next()?;
+    let file = FILES.iter().position(|f| f.starts_with(file))? as u8;
+    let rank = parts.next()?.to_digit(10).filter(|r| (1..=8).contains(r))? as u8;
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(BOARD_MAP[(8 * (8 - rank) + file) as usize])
+}
+
+/// Convert an index on the board to a square like "e4"
+fn square_name(index: PieceIndex) -> String {
+    let file = index & 7;
+    let rank = 8 - ((index >> 4) + 1) + 1;
+
+    let file_letter = FILES[file as usize];
+
+    let mut notation = String::new();
+
+    notation.push_str(file_letter);
+    notation.push_str(rank.to_string().as_str());
+
+    notation
+}
+
+/// The 0x88 squares of the king and rook after castling with a right, see `castling_rooks`
+fn castled_squares(king_idx: PieceIndex, right: usize) -> (PieceIndex, PieceIndex) {
+    let (king_to, rook_to) =
+        position::castled_squares(square::from_0x88(king_idx), right.is_multiple_of(2));
+
+    (square::to_0x88(king_to), square::to_0x88(rook_to))
 }
 
 #[derive(Debug)]
@@ -146,31 +109,114 @@ struct HistoryEntry {
     from_piece: PieceType,
     to_piece: PieceType,
     kings: King,
-    castle: bool,
+    /// the squares of the rook before and after castling
+    castle: Option<(PieceIndex, PieceIndex)>,
     capture: bool,
     turn: u8,
     last_turn: u8,
     en_passant_capture: bool,
     en_passant_move: bool,
     promotion: bool,
-    half_moves: u8,
-    full_moves: u8,
+    /// the piece the pawn promoted to, set by `play_move`
+    promoted_to: Option<PieceType>,
+    half_moves: u16,
+    full_moves: u16,
     en_passant_square: Option<String>,
+    en_passant_hash: u64,
     can_white_king_side_castle: bool,
     can_white_queen_side_castle: bool,
     can_black_king_side_castle: bool,
     can_black_queen_side_castle: bool,
 }
 
-#[derive(Debug)]
+#[allow(non_snake_case)]
+pub mod MoveFlag {
+    pub type MoveFlags = u8;
+
+    pub const NORMAL: MoveFlags = 0; //             00000000
+    pub const CAPTURE: MoveFlags = 1; //            00000001
+    pub const BIG_PAWN: MoveFlags = 2; //           00000010
+    pub const EN_PASSANT: MoveFlags = 4; //         00000100
+    pub const PROMOTION: MoveFlags = 8; //          00001000
+    pub const KING_SIDE_CASTLE: MoveFlags = 16; //  00010000
+    pub const QUEEN_SIDE_CASTLE: MoveFlags = 32; // 00100000
+}
+
+pub use MoveFlag::MoveFlags;
+
+/// A legal move in the current position.
+///
+/// Pieces are stored with their color but without the moved mask, e.g. `BLACK_PAWN`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Move {
-    from: PieceIndex,
-    to: PieceIndex,
-    // in_check: bool,
-    // opponent_in_check: bool,
+    pub(crate) from: PieceIndex,
+    pub(crate) to: PieceIndex,
+    /// the piece that is moving
+    pub(crate) piece: PieceType,
+    /// the piece that is captured, this is the enemy pawn for en passant captures
+    pub(crate) captured: Option<PieceType>,
+    /// the piece the pawn promotes to
+    pub(crate) promotion: Option<PieceType>,
+    pub(crate) flags: MoveFlags,
 }
 
-// impl Vec<Move> {}
+impl Move {
+    pub fn is_capture(&self) -> bool {
+        self.flags & MoveFlag::CAPTURE != 0
+    }
+
+    pub fn is_en_passant(&self) -> bool {
+        self.flags & MoveFlag::EN_PASSANT != 0
+    }
+
+    pub fn is_promotion(&self) -> bool {
+        self.flags & MoveFlag::PROMOTION != 0
+    }
+
+    pub fn is_castle(&self) -> bool {
+        self.flags & (MoveFlag::KING_SIDE_CASTLE | MoveFlag::QUEEN_SIDE_CASTLE) != 0
+    }
+
+    pub fn from_square(&self) -> Square {
+        square::from_0x88(self.from)
+    }
+
+    pub fn to_square(&self) -> Square {
+        square::from_0x88(self.to)
+    }
+
+    pub fn moved_piece(&self) -> chess2::Piece {
+        chess2::Piece::from_piece_type(self.piece).expect("a move has a piece")
+    }
+
+    pub fn captured_piece(&self) -> Option<chess2::Piece> {
+        self.captured.and_then(chess2::Piece::from_piece_type)
+    }
+
+    pub fn promotion_role(&self) -> Option<Role> {
+        self.promotion.and_then(Role::from_piece_type)
+    }
+
+    pub fn flags(&self) -> MoveFlags {
+        self.flags
+    }
+
+    /// The long algebraic notation used by the UCI protocol, e.g. `e2e4`, `e1g1` for
+    /// castling or `e7e8q` for promotions
+    pub fn to_uci(&self) -> String {
+        let mut uci = format!("{}{}", square_name(self.from), square_name(self.to));
+
+        match self.promotion.map(|p| p & !BLACK) {
+            Some(QUEEN) => uci.push('q'),
+            Some(ROOK) => uci.push('r'),
+            Some(BISHOP) => uci.push('b'),
+            Some(KNIGHT) => uci.push('n'),
+            _ => {}
+        }
+
+        uci
+    }
+}
 
 #[derive(Debug, Clone)]
 struct King {
@@ -179,13 +225,16 @@ struct King {
 }
 #[derive(Debug)]
 pub struct Chess {
-    pub board: [PieceType; BOARD_SIZE as usize],
+    board: [PieceType; BOARD_SIZE as usize],
 
     /// 0 = white, 128 = black
     turn: u8,
 
     history: Vec<HistoryEntry>,
 
+    /// the position the history starts from
+    starting_fen: String,
+
     /// the kings' indices on the board
     kings: King,
 
@@ -199,23 +248,49 @@ pub struct Chess {
     pub can_black_king_side_castle: bool,
     pub can_black_queen_side_castle: bool,
 
-    /// Record the number of each position.
-    /// If any position happens 3 times, the game is declared draw
-    unique_positions: HashMap<String, u8>,
+    /// the squares of the rooks for white king side, white queen side, black king side and
+    /// black queen side castling, which are only in the corners in standard chess
+    pub(crate) castling_rooks: [PieceIndex; 4],
+
+    /// Fischer Random chess, see `set_chess960`
+    chess960: bool,
+
+    /// `hash()` of the position before each move of `history`, pushed and popped with it
+    positions: Vec<u64>,
+
+    /// a resignation, timeout, agreed or claimed draw and the length of `history` when it
+    /// happened, it no longer applies once that move is undone
+    declared_outcome: Option<(usize, Outcome)>,
+
+    /// Zobrist key of the pieces on the board, updated by `set`
+    pieces_hash: u64,
 
-    half_moves: u8,
-    full_moves: u8,
+    /// the squares of each role, indexed by `Role` and updated by `set`, so `Position::from`
+    /// doesn't have to scan the board
+    pub(crate) roles: [Bitboard; 6],
+    /// the squares of each color, indexed by `Color` and updated by `set`
+    pub(crate) colors: [Bitboard; 2],
+
+    half_moves: u16,
+    full_moves: u16,
 
     lastest_en_passant_square: Option<String>,
 
+    /// Zobrist key of the en passant square if a pawn can take en passant, 0 otherwise. It is
+    /// decided when the pawn moves 2 squares, see `en_passant_key`.
+    en_passant_hash: u64,
+
     last_turn: u8,
 
     pub captures: u64,
     pub castles: u64,
-    pub checks: u64,
     pub promotions: u64,
+}
 
-    pub moves: HashMap<String, u64>,
+impl Default for Chess {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Chess {
@@ -224,6 +299,7 @@ impl Chess {
             board: [EMPTY; BOARD_SIZE as usize],
             turn: WHITE,
             history: vec![],
+            starting_fen: String::new(),
             kings: King { white: 1, black: 2 },
             white_captures: vec![],
             black_captures: vec![],
@@ -231,291 +307,74 @@ impl Chess {
             can_white_queen_side_castle: true,
             can_black_king_side_castle: true,
             can_black_queen_side_castle: true,
-            unique_positions: HashMap::new(),
-            moves: HashMap::new(),
+            castling_rooks: [119, 112, 7, 0],
+            chess960: false,
+            positions: vec![],
+            declared_outcome: None,
+            pieces_hash: 0,
+            roles: [0; 6],
+            colors: [0; 2],
 
             half_moves: 0,
             full_moves: 0,
             lastest_en_passant_square: None,
+            en_passant_hash: 0,
             last_turn: WHITE,
             captures: 0,
             castles: 0,
-            checks: 0,
             promotions: 0,
         }
     }
-    // DISREGARD
-    pub fn move_piece(&mut self, move_notation: &str) -> Result<String, MoveError> {
-        if move_notation.is_empty() {
-            return Err(MoveError::InvalidPieceToMove);
-        }
-
-        let parts: Vec<char> = move_notation.chars().collect();
-        let move_regex =
-            Regex::new(r"^([KQRBN])?([a-h]|[1-8])?(x)?([a-h])([1-8])(=)?([KQRBN])?([+#])?$")
-                .unwrap();
-
-        // TODO is there anyway we can reduce the noise?
-
-        // king-side castle
-        if move_notation == "O-O" {
-            if self.turn == WHITE {
-                if !self.can_white_king_side_castle {
-                    return Err(MoveError::IllegalKingSideCastle);
-                }
-
-                let legal_moves = self.inner_moves(self.kings.white);
-
-                // let m = Move {
-                //     from: self.kings.white,
-                //     to: 118,
-                //     opponent_in_check: false,
-                // };
-
-                if legal_moves.contains(&118) {
-                    self.inner_move_piece(self.kings.white, 118);
-                } else {
-                    return Err(MoveError::IllegalKingSideCastle);
-                }
-            } else {
-                if !self.can_black_king_side_castle {
-                    return Err(MoveError::IllegalKingSideCastle);
-                }
-
-                let legal_moves = self.inner_moves(self.kings.black);
-
-                if legal_moves.contains(&6) {
-                    self.inner_move_piece(self.kings.black, 6);
-                } else {
-                    return Err(MoveError::IllegalKingSideCastle);
-                }
-            }
-        // queen side castle
-        } else if move_notation == "O-O-O" {
-            if self.turn == WHITE {
-                if !self.can_white_queen_side_castle {
-                    return Err(MoveError::IllegalQueenSideCastle);
-                }
-
-                let legal_moves = self.inner_moves(self.kings.white);
-
-                if legal_moves.contains(&114) {
-                    self.inner_move_piece(self.kings.white, 114);
-                } else {
-                    return Err(MoveError::IllegalQueenSideCastle);
-                }
-            } else {
-                if !self.can_black_queen_side_castle {
-                    return Err(MoveError::IllegalQueenSideCastle);
-                }
-
-                let legal_moves = self.inner_moves(self.kings.black);
-
-                if legal_moves.contains(&2) {
-                    self.inner_move_piece(self.kings.black, 2);
-                } else {
-                    return Err(MoveError::IllegalQueenSideCastle);
-                }
-            }
-        } else {
-            // TODO error for this
-            let captures = move_regex
-                .captures(move_notation)
-                .expect("Invalid move notation");
-
-            let piece_identifer = captures.get(2).map_or("", |m| m.as_str());
-            let is_capture = captures.get(3).map_or(false, |_| true);
-            let to_file = captures.get(4).map_or("", |m| m.as_str());
-            let to_rank = captures.get(5).map_or("", |m| m.as_str());
-            let is_promotion = captures.get(6).map_or(false, |_| true);
-            let promotion_piece = captures.get(7).map_or("", |m| m.as_str());
-
-            let mut from_idx: Option<PieceIndex> = None;
-            let idx = self
-                .convert_algebraic_notation_to_index(format!("{}{}", to_file, to_rank).as_str());
-
-            let to_idx = BOARD_MAP[idx as usize];
-            let target_piece = self.get(to_idx);
-
-            // en passant only if piece is pawn
-            if is_capture
-                && ((self.is_friendly(target_piece) && target_piece != EN_PASSANT_SQUARE)
-                    || target_piece == EMPTY)
-            {
-                return Err(MoveError::IllegalCapture);
-            }
-
-            let mut target_file = None;
-            let mut target_rank = None;
 
-            if !piece_identifer.is_empty() {
-                if is_number(piece_identifer) {
-                    target_rank = Some(piece_identifer.parse::<u8>().unwrap());
-                } else {
-                    target_file =
-                        Some(FILES.iter().position(|f| f.eq(&piece_identifer)).unwrap() as u8);
-                }
-            }
-
-            if parts[0].is_uppercase() {
-                // [a-h][file-rank-identifier][a-h][file]
-                // [a-h]x[a-h][file]
-                // [a-h][file-rank-identifier]x[a-h][file]
-
-                let mut count = 0;
-                let target_type = match parts[0] {
-                    'K' => KING,
-                    'Q' => QUEEN,
-                    'R' => ROOK,
-                    'B' => BISHOP,
-                    'N' => KNIGHT,
-                    _ => {
-                        return Err(MoveError::UnknownPiece);
-                    }
-                };
+    /// Start a Chess960 game from one of its starting positions, numbered from 0 to 959 as
+    /// in `chess960::starting_fen`
+    pub fn new_chess960(index: u16) -> Option<Self> {
+        let mut chess = Chess::new();
 
-                for idx in 0..BOARD_SIZE {
-                    if !self.is_on_board(idx) {
-                        continue;
-                    }
+        chess.set_chess960(true);
+        chess.load_fen(chess960::starting_fen(index)?);
 
-                    let piece = self.get(idx);
-                    let piece_type = self.get_type(piece);
+        Some(chess)
+    }
 
-                    if piece_type == target_type && self.is_friendly(piece) {
-                        count += 1;
+    /// Play Fischer Random chess, where the king and rooks can start on any file.
+    ///
+    /// Castling moves are written in UCI notation as the king taking its own rook, e.g. `b1a1`,
+    /// and `KQkq` in FEN mean the outermost rooks (X-FEN). Set it before loading a FEN, which
+    /// enables it by itself if the castling rights are written with the rook files
+    /// (Shredder-FEN).
+    pub fn set_chess960(&mut self, chess960: bool) {
+        self.chess960 = chess960;
+    }
 
-                        // TODO use .moves instead of .inner_moves so we don't have to convert the index
-                        let _moves = self.inner_moves(idx);
-                        let file = idx & 7;
-                        let rank = 8 - ((idx >> 4) + 1) + 1;
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
 
-                        if !_moves.contains(&to_idx) {
-                            continue;
-                        }
+    /// Play a move written in Standard Algebraic Notation and return the notation with
+    /// its check or checkmate suffix
+    pub fn move_piece(&mut self, move_notation: &str) -> Result<String, MoveError> {
+        let _move = self.parse_san(move_notation)?;
 
-                        if let Some(target_file) = target_file {
-                            if file == target_file {
-                                if count >= 2 && _moves.contains(&to_idx) {
-                                    return Err(MoveError::AmbiguousMoveNotation);
-                                }
-                                from_idx = Some(idx);
-                            }
-                        } else if let Some(target_rank) = target_rank {
-                            if rank == target_rank {
-                                if count >= 2 && _moves.contains(&to_idx) {
-                                    return Err(MoveError::AmbiguousMoveNotation);
-                                }
-                                from_idx = Some(idx);
-                            }
-                        } else {
-                            if count >= 2 && _moves.contains(&to_idx) && from_idx != None {
-                                // if the second piece can also move to that location, then we panic
-                                // because we don't know which piece to move
-                                return Err(MoveError::AmbiguousMoveNotation);
-                            }
+        self.play_move(_move);
 
-                            from_idx = Some(idx);
-                        }
-                    }
-                }
+        let mut new_notation = move_notation
+            .trim_end_matches(['!', '?'])
+            .trim_end_matches(['+', '#'])
+            .to_string();
 
-                if let Some(from_idx) = from_idx {
-                    self.inner_move_piece(from_idx, to_idx)
-                } else {
-                    println!("{} {} {:?}", move_notation, self.get_fen(), self.board);
-                    return Err(MoveError::InvalidPieceToMove);
-                }
+        if self.in_check() {
+            if self.generate_legal_moves().is_empty() {
+                new_notation.push('#');
             } else {
-                let rank = 8 - ((to_idx >> 4) + 1) + 1;
-
-                if is_promotion && (rank > 1 && rank < 8) {
-                    return Err(MoveError::InvalidPromotion);
-                }
-
-                if rank == 0 || rank == 8 {
-                    if !is_promotion {
-                        return Err(MoveError::InvalidPromotion);
-                    }
-
-                    if is_promotion {
-                        if promotion_piece.is_empty() {
-                            return Err(MoveError::InvalidPromotion);
-                        }
-                    }
-                }
-
-                // pawn move
-                for idx in 0..BOARD_SIZE {
-                    if !self.is_on_board(idx) {
-                        continue;
-                    }
-
-                    let piece = self.get(idx);
-                    let piece_type = self.get_type(piece);
-
-                    if piece_type == PAWN && self.is_friendly(piece) {
-                        let _moves = self.inner_moves(idx);
-
-                        if !_moves.contains(&to_idx) {
-                            // println!("{} {:?}", idx, _moves);
-                            continue;
-                        }
-                        from_idx = Some(idx);
-                    }
-                }
-
-                if let Some(from_idx) = from_idx {
-                    self.inner_move_piece(from_idx, to_idx);
-
-                    if is_promotion {
-                        let piece = match promotion_piece {
-                            "K" => KING,
-                            "Q" => QUEEN,
-                            "R" => ROOK,
-                            "B" => BISHOP,
-                            "N" => KNIGHT,
-                            _ => return Err(MoveError::InvalidPromotion),
-                        };
-
-                        self.set(piece, to_idx);
-                    }
-                } else {
-                    return Err(MoveError::InvalidPieceToMove);
-                }
+                new_notation.push('+');
             }
         }
 
-        if self.turn == BLACK {
-            self.full_moves += 1;
-        }
-
-        let fen = self.get_fen();
-        let position = fen.split(" ").collect::<Vec<&str>>()[0];
-
-        // *self
-        //     .unique_positions
-        //     .entry(position.to_string())
-        //     .or_insert(0) += 1;
-        self.update_castling_rights();
-        self.change_turn();
-        // if self.is_draw() {
-        //     println!("game drawn");
-        // }
-
-        let mut new_notation = move_notation.to_string();
-
-        // if self.is_checkmate() {
-        //     println!("game over by checkmate");
-        //     new_notation.push('#');
-        // } else if self.in_check() {
-        //     new_notation.push('+');
-        // }
-
         Ok(new_notation)
     }
 
-    pub fn inner_move_piece(&mut self, from_idx: PieceIndex, to_idx: PieceIndex) {
+    fn inner_move_piece(&mut self, from_idx: PieceIndex, to_idx: PieceIndex) {
         if self.is_on_board(to_idx) {
             let mut piece = self.get(from_idx);
 
@@ -526,35 +385,45 @@ impl Chess {
             let to_piece = self.get(to_idx);
             let piece_type = self.remove_color(piece);
 
+            // where the piece ends up, which is not `to_idx` when castling in Chess960
+            let mut destination = to_idx;
+
             let mut history_entry = HistoryEntry {
                 from_idx,
                 to_idx,
                 from_piece: self.get(from_idx),
                 to_piece,
                 kings: self.kings.clone(),
-                castle: false,
+                castle: None,
                 capture: false,
                 en_passant_capture: false,
                 en_passant_move: false,
                 promotion: false,
+                promoted_to: None,
                 turn: self.turn,
                 last_turn: self.last_turn,
                 half_moves: self.half_moves,
                 full_moves: self.full_moves,
                 en_passant_square: self.lastest_en_passant_square.clone(),
+                en_passant_hash: self.en_passant_hash,
                 can_white_king_side_castle: self.can_white_king_side_castle,
                 can_white_queen_side_castle: self.can_white_queen_side_castle,
                 can_black_king_side_castle: self.can_black_king_side_castle,
                 can_black_queen_side_castle: self.can_black_queen_side_castle,
             };
 
+            self.positions.push(self.hash());
+
+            // a pawn can only take en passant right after the double push
+            self.en_passant_hash = 0;
+
             if to_piece != EN_PASSANT_SQUARE {
                 self.clear_latest_en_passant_square();
             }
 
             if piece_type == PAWN {
                 // set the pawn to moved
-                piece = piece | MOVED_MASK;
+                piece |= MOVED_MASK;
                 // if it has moved 2 squares, update en passant square
                 if to_idx.abs_diff(from_idx) == 32 {
                     // make the square behind the pawn an en passant square
@@ -596,41 +465,36 @@ impl Chess {
                 self.reset_half_moves();
                 self.lastest_en_passant_square = None;
             } else if piece_type == KING {
-                self.update_kings_position(to_idx);
+                piece |= MOVED_MASK;
 
-                piece = piece | MOVED_MASK;
-
-                let (can_king_side_castle, can_queen_side_castle) = self.get_castling_rights();
-
-                if can_king_side_castle || can_queen_side_castle {
-                    if self.is_king_side_castling(from_idx, to_idx) && can_king_side_castle {
-                        self.set(Piece::ROOK | self.turn | MOVED_MASK, to_idx - 1);
-                        self.set(Piece::EMPTY, to_idx + 1);
-                        history_entry.castle = true;
-                        self.castles += 1;
-                    } else if self.is_queen_side_castling(from_idx, to_idx) && can_queen_side_castle
-                    {
-                        self.set(Piece::ROOK | self.turn | MOVED_MASK, to_idx + 1);
-                        self.set(Piece::EMPTY, to_idx - 2);
-                        history_entry.castle = true;
-                        self.castles += 1;
-                    }
+                if let Some(right) = self.castling_right(from_idx, to_idx) {
+                    let rook_idx = self.castling_rooks[right];
+                    let (king_to, rook_to) = castled_squares(from_idx, right);
+
+                    // the rook is put back on the board once the king has moved
+                    self.set(Piece::EMPTY, rook_idx);
+                    destination = king_to;
+                    history_entry.castle = Some((rook_idx, rook_to));
+                    self.castles += 1;
                 }
+
+                self.update_kings_position(destination);
             } else if piece_type == MOVED_KING {
                 self.update_kings_position(to_idx);
             } else if piece_type == ROOK {
-                piece = piece | MOVED_MASK;
-            } else {
-                self.half_moves += 1;
+                piece |= MOVED_MASK;
             }
 
-            if self.is_occupied(to_idx) {
-                if !self.is_friendly(to_piece) {
-                    self.capture(to_piece);
-                    history_entry.capture = true;
-                    self.reset_half_moves();
-                    self.captures += 1;
-                }
+            // only pawn moves and captures reset the halfmove clock
+            if piece_type != PAWN && piece_type != MOVED_PAWN {
+                self.half_moves = self.half_moves.saturating_add(1);
+            }
+
+            if self.is_occupied(to_idx) && !self.is_friendly(to_piece) {
+                self.capture(to_piece);
+                history_entry.capture = true;
+                self.reset_half_moves();
+                self.captures += 1;
             }
 
             // // check if we are capturing a piece
@@ -642,8 +506,13 @@ impl Chess {
                 self.lastest_en_passant_square = None;
             }
 
-            self.set(piece, to_idx);
+            // the king and rook can swap squares in Chess960, so the origin is emptied first
             self.set(Piece::EMPTY, from_idx);
+            self.set(piece, destination);
+
+            if let Some((_, rook_to)) = history_entry.castle {
+                self.set(Piece::ROOK | self.turn | MOVED_MASK, rook_to);
+            }
 
             self.history.push(history_entry);
         } else {
@@ -654,15 +523,30 @@ impl Chess {
     /// Return the piece on the square
     fn get(&self, square_idx: PieceIndex) -> PieceIndex {
         if !self.is_on_board(square_idx) || square_idx > 150 {
-            println!("lmao");
             panic!("square out of bound");
         }
 
         self.board[square_idx as usize]
     }
 
+    /// The piece on a square, without the flags `board` keeps
+    pub fn piece_at(&self, square: Square) -> Option<chess2::Piece> {
+        chess2::Piece::from_piece_type(self.board[square::to_0x88(square) as usize])
+    }
+
+    /// Every square of the board with the piece on it, indexed by `Square::index`
+    pub fn pieces(&self) -> [Option<chess2::Piece>; 64] {
+        let mut pieces = [None; 64];
+
+        for square in Square::all() {
+            pieces[square.index()] = self.piece_at(square);
+        }
+
+        pieces
+    }
+
     /// Put a piece on a square
-    pub fn set(&mut self, piece: PieceType, square_idx: PieceIndex) {
+    pub(crate) fn set(&mut self, piece: PieceType, square_idx: PieceIndex) {
         if !self.is_on_board(square_idx) || square_idx > 150 {
             panic!("square out of bound");
         }
@@ -675,124 +559,42 @@ impl Chess {
             self.kings.black = square_idx;
         }
 
+        self.toggle(self.board[square_idx as usize], square_idx);
+        self.toggle(piece, square_idx);
+
         self.board[square_idx as usize] = piece;
 
         // self.update_castling_rights();
     }
 
-    // DISREGARD
-    pub fn moves(&mut self, square: &str) -> Vec<String> {
-        let square_idx = BOARD_MAP[self.convert_algebraic_notation_to_index(square) as usize];
+    /// Add or remove a piece in `pieces_hash` and the bitboards, empty squares are left alone
+    fn toggle(&mut self, piece: PieceType, square_idx: PieceIndex) {
+        if let Some(role) = Role::from_piece_type(piece) {
+            let color = Color::from_mask(piece);
+            let square = square::from_0x88(square_idx);
 
-        if !self.is_on_board(square_idx) {
-            panic!("invalid square");
+            self.pieces_hash ^= zobrist::piece(color, role, square);
+            self.roles[role as usize] ^= bit(square);
+            self.colors[color as usize] ^= bit(square);
         }
+    }
 
-        let moves = self.inner_moves(square_idx);
-
-        let piece = self.get(square_idx);
-        let piece_type = self.get_type(piece);
-
-        let prefix = match piece_type {
-            KING => "K",
-            QUEEN => "Q",
-            ROOK => "R",
-            BISHOP => "B",
-            KNIGHT => "N",
-            PAWN => "",
-            _ => {
-                panic!("invalid piece")
-            }
-        };
-
-        let mut a: Vec<String> = moves
-            .iter()
-            .map(|m| {
-                // TODO: use a Move struct to make our lives easier
-
-                if *m == 118 && piece_type == KING {
-                    return String::from("O-O");
-                } else if *m == 114 && piece_type == KING {
-                    return String::from("O-O-O");
-                } else {
-                    let to = self.get(*m);
-                    let rank = 8 - ((*m >> 4) + 1) + 1;
-
-                    if (rank == 1 || rank == 8) && piece_type == PAWN {
-                        return m.to_string();
-                        // return format!(
-                        //     "{}={}",
-                        //     FILES[file as usize],
-                        //     self.convert_index_algebraic_notation(*m)
-                        // );
-                    }
-
-                    if to == EN_PASSANT_SQUARE && piece_type == PAWN {
-                        let file = square_idx & 7;
-
-                        return format!(
-                            "{}x{}",
-                            FILES[file as usize],
-                            self.convert_index_algebraic_notation(*m)
-                        );
-                    }
-
-                    if self.is_occupied(*m) && !self.is_friendly(to) {
-                        return format!("{}x{}", prefix, self.convert_index_algebraic_notation(*m));
-                    }
-
-                    return format!("{}{}", prefix, self.convert_index_algebraic_notation(*m));
-                }
-            })
-            .collect();
-
-        let mut t_idx: Option<usize> = None;
-
-        let mut temp_a = a.clone();
-
-        // for (i, b) in a.iter().enumerate() {
-        //     if is_number(b) {
-        //         let idx: u8 = b.parse().unwrap();
-
-        //         let rank = 8 - ((idx >> 4) + 1) + 1;
-
-        //         if (rank == 1 || rank == 8) && piece_type == PAWN {
-        //             temp_a.push(format!(
-        //                 "{}={}",
-        //                 self.convert_index_algebraic_notation(idx),
-        //                 "Q"
-        //             ));
-
-        //             temp_a.push(format!(
-        //                 "{}={}",
-        //                 self.convert_index_algebraic_notation(idx),
-        //                 "R"
-        //             ));
-
-        //             temp_a.push(format!(
-        //                 "{}={}",
-        //                 self.convert_index_algebraic_notation(idx),
-        //                 "N"
-        //             ));
-        //             temp_a.push(format!(
-        //                 "{}={}",
-        //                 self.convert_index_algebraic_notation(idx),
-        //                 "B"
-        //             ));
-        //         }
-
-        //         t_idx = Some(i as usize);
-        //     }
-        // }
+    /// Return the legal moves of the piece on the square in Standard Algebraic Notation, none
+    /// if the square is empty or the piece can't move this turn
+    pub fn moves(&mut self, square: Square) -> Vec<String> {
+        let square_idx = square::to_0x88(square);
 
-        // if let Some(t_idx) = t_idx {
-        //     temp_a.remove(t_idx);
-        // }
+        if !self.is_occupied(square_idx) || !self.is_friendly(self.get(square_idx)) {
+            return vec![];
+        }
 
-        a
+        self.legal_moves_from(square_idx)
+            .into_iter()
+            .map(|_move| self.san(_move))
+            .collect()
     }
 
-    pub fn inner_moves(&mut self, square_idx: PieceIndex) -> Vec<PieceIndex> {
+    fn inner_moves(&mut self, square_idx: PieceIndex) -> Vec<PieceIndex> {
         let piece = self.get(square_idx);
 
         if !self.is_friendly(piece) {
@@ -803,6 +605,9 @@ impl Chess {
             panic!("can't generate moves for empty squares")
         }
 
+        // the bitboard position knows which moves are legal without playing them
+        let position = Position::from(&*self);
+
         let inner_moves = match self.remove_color(piece) {
             PAWN => self.generate_pawn_moves(square_idx),
             MOVED_PAWN => self.generate_pawn_moves(square_idx),
@@ -810,38 +615,25 @@ impl Chess {
             ROOK => self.generate_sliding_moves(square_idx, ROOK_DELTAS.to_vec()),
             QUEEN => self.generate_sliding_moves(square_idx, QUEEN_DELTAS.to_vec()),
             KNIGHT => self.generate_knight_moves(square_idx),
-            KING => self.generate_king_moves(square_idx, KING_DELTAS.to_vec()),
-            MOVED_KING => self.generate_king_moves(square_idx, MOVED_KING_DELTAS.to_vec()),
+            KING => {
+                let mut inner_moves = self.generate_king_moves(square_idx, KING_DELTAS.to_vec());
+                inner_moves.extend(position.castling_moves().iter().map(|_move| _move.to));
+                inner_moves
+            }
+            MOVED_KING => self.generate_king_moves(square_idx, KING_DELTAS.to_vec()),
             MOVED_ROOK => self.generate_sliding_moves(square_idx, ROOK_DELTAS.to_vec()),
             _ => vec![],
         };
 
-        let mut legal_moves: Vec<PieceIndex> = vec![];
-
-        for idx in inner_moves {
-            let to_idx = idx;
-            // play the move
-            self.inner_move_piece(square_idx, to_idx as u8);
-
-            if !self.in_check() {
-                // println!("{:?}", self.board);
-                legal_moves.push(to_idx);
-                // legal_moves.push(Move {
-                //     // Optimize this later?
-                //     // opponent_in_check: self.opponent_in_check(),
-                //     from: square_idx,
-                //     to: to_idx,
-                // });
-            }
-
-            // revert the move
-            self.undo();
-        }
+        let from = square::from_0x88(square_idx);
 
-        legal_moves
+        inner_moves
+            .into_iter()
+            .filter(|&to_idx| position.is_legal(from, square::from_0x88(to_idx)))
+            .collect()
     }
 
-    pub fn generate_pawn_moves(&mut self, square_idx: PieceIndex) -> Vec<u8> {
+    fn generate_pawn_moves(&mut self, square_idx: PieceIndex) -> Vec<u8> {
         let mut inner_moves = vec![];
 
         let pawn = self.get(square_idx);
@@ -867,7 +659,7 @@ impl Chess {
             if self.is_on_board(destination_idx) {
                 let piece = self.get(destination_idx);
 
-                let rank = 8 - ((destination_idx as u8 >> 4) + 1) + 1;
+                let rank = 8 - ((destination_idx >> 4) + 1) + 1;
 
                 if rank == 1 || rank == 8 {
                     // println!("hi");
@@ -917,7 +709,8 @@ impl Chess {
         inner_moves
     }
 
-    pub fn generate_king_moves(&mut self, square_idx: PieceIndex, deltas: Vec<i8>) -> Vec<u8> {
+    /// The squares next to the king, castling moves come from `Position::castling_moves`
+    fn generate_king_moves(&mut self, square_idx: PieceIndex, deltas: Vec<i8>) -> Vec<u8> {
         let mut inner_moves: Vec<PieceIndex> = vec![];
 
         for delta in deltas {
@@ -926,119 +719,10 @@ impl Chess {
 
             if self.is_on_board(destination_idx) {
                 let piece = self.get(destination_idx);
-                let is_friendly = self.is_friendly(piece);
-
-                if self.is_occupied(destination_idx) {
-                    let is_castling_move = self.is_king_side_castling(square_idx, destination_idx)
-                        || self.is_queen_side_castling(square_idx, destination_idx);
-
-                    // we can only castle if the square is empty
-                    if is_castling_move {
-                        continue;
-                    }
-
-                    // if enemy piece, we can capture it
-                    if !is_friendly || piece == EN_PASSANT_SQUARE {
-                        inner_moves.push(destination_idx);
-                    }
-                } else {
-                    let is_king_side_castling =
-                        self.is_king_side_castling(square_idx, destination_idx);
-
-                    let is_queen_side_castling =
-                        self.is_queen_side_castling(square_idx, destination_idx);
-
-                    let (can_king_side_castle, can_queen_side_castle) = self.get_castling_rights();
-
-                    // if try to castle without castling rights, skip
-
-                    if is_king_side_castling && !can_king_side_castle {
-                        continue;
-                    }
-
-                    if is_queen_side_castling && !can_queen_side_castle {
-                        continue;
-                    }
-
-                    if is_king_side_castling || is_queen_side_castling {
-                        let rook_idx: PieceIndex = if is_king_side_castling {
-                            destination_idx + 1
-                        } else if is_queen_side_castling {
-                            destination_idx - 2
-                        } else {
-                            panic!("failed to castle")
-                        };
-
-                        let piece = self.get(rook_idx);
-                        let mut is_checked = false;
-                        let mut is_blocked = false;
-                        let mut idx = square_idx;
-                        let mut idx_2 = square_idx;
-
-                        let block_range = if is_king_side_castling {
-                            0..3
-                        } else if is_queen_side_castling {
-                            0..4
-                        } else {
-                            0..2
-                        };
-
-                        let attack_range = if is_king_side_castling {
-                            0..3
-                        } else if is_queen_side_castling {
-                            0..3
-                        } else {
-                            0..2
-                        };
-
-                        for _ in block_range {
-                            // let is_attacked = self.is_attacked(idx);
-
-                            // if is_attacked {
-                            //     is_checked = true;
-                            // }
-
-                            if idx_2 != square_idx && self.get(idx_2) != EMPTY {
-                                is_blocked = true;
-                            }
-
-                            if is_king_side_castling {
-                                idx_2 += 1;
-                            }
-
-                            if is_queen_side_castling {
-                                idx_2 -= 1;
-                            }
-                        }
-
-                        for _ in attack_range {
-                            let is_attacked = self.is_attacked(idx);
-
-                            if is_attacked {
-                                is_checked = true;
-                            }
-
-                            if is_king_side_castling {
-                                idx += 1;
-                            }
 
-                            if is_queen_side_castling {
-                                idx -= 1;
-                            }
-                        }
-
-                        if piece != EMPTY
-                            && self.remove_color(piece) == ROOK
-                            && self.is_friendly(piece)
-                            && !is_checked
-                            && !is_blocked
-                        {
-                            inner_moves.push(destination_idx);
-                        }
-                        // self.update_castling_rights();
-                    } else {
-                        inner_moves.push(destination_idx);
-                    }
+                // if enemy piece, we can capture it
+                if !self.is_occupied(destination_idx) || !self.is_friendly(piece) {
+                    inner_moves.push(destination_idx);
                 }
             }
         }
@@ -1046,7 +730,7 @@ impl Chess {
         inner_moves
     }
 
-    pub fn generate_knight_moves(&mut self, square_idx: PieceIndex) -> Vec<u8> {
+    fn generate_knight_moves(&mut self, square_idx: PieceIndex) -> Vec<u8> {
         let mut inner_moves: Vec<PieceIndex> = vec![];
 
         for delta in KNIGHT_DELTAS {
@@ -1056,10 +740,8 @@ impl Chess {
             if self.is_on_board(destination_idx as u8) {
                 let piece = self.get(destination_idx as PieceIndex);
 
-                if self.is_occupied(destination_idx as PieceIndex) {
-                    if self.is_friendly(piece) {
-                        continue;
-                    }
+                if self.is_occupied(destination_idx as PieceIndex) && self.is_friendly(piece) {
+                    continue;
                 }
 
                 inner_moves.push(destination_idx as PieceIndex);
@@ -1069,7 +751,7 @@ impl Chess {
         inner_moves
     }
 
-    pub fn generate_sliding_moves(&mut self, square_idx: PieceIndex, deltas: Vec<i8>) -> Vec<u8> {
+    fn generate_sliding_moves(&mut self, square_idx: PieceIndex, deltas: Vec<i8>) -> Vec<u8> {
         let mut inner_moves: Vec<PieceIndex> = vec![];
 
         for delta in deltas {
@@ -1104,22 +786,18 @@ impl Chess {
     // saving a FEN string of the board everytime a move is made and use the previous FEN when undoing inner_moves
     pub fn undo(&mut self) {
         if let Some(old) = self.history.pop() {
-            if old.castle {
-                let HistoryEntry {
-                    to_idx, from_idx, ..
-                } = old;
-
-                // TODO: just hardcode the castle squares
-                let king_side = self.is_king_side_castling(from_idx, to_idx);
-                let queen_side = self.is_queen_side_castling(from_idx, to_idx);
-
-                if king_side {
-                    self.set(ROOK | old.turn, to_idx + 1);
-                    self.set(EMPTY, from_idx + 1);
-                } else if queen_side {
-                    self.set(ROOK | old.turn, to_idx - 2);
-                    self.set(EMPTY, from_idx - 1);
-                }
+            self.positions.pop();
+
+            if let Some((rook_idx, rook_to)) = old.castle {
+                // the rook ends up on the f or d file
+                let right = castling_right(Color::from_mask(old.turn), rook_to & 7 == 5);
+                let (king_to, _) = castled_squares(old.from_idx, right);
+
+                // the king is put back below with `from_piece`, and so is the rook with
+                // `to_piece` in Chess960, where the king takes its own rook
+                self.set(EMPTY, king_to);
+                self.set(EMPTY, rook_to);
+                self.set(ROOK | old.turn, rook_idx);
             }
 
             if old.capture {
@@ -1178,6 +856,7 @@ impl Chess {
             // self.update_castling_rights();
 
             self.lastest_en_passant_square = old.en_passant_square;
+            self.en_passant_hash = old.en_passant_hash;
             if let Some(ep_square) = &self.lastest_en_passant_square {
                 let ep_idx = BOARD_MAP
                     [self.convert_algebraic_notation_to_index(ep_square.as_str()) as usize];
@@ -1213,24 +892,14 @@ impl Chess {
         let half_moves = self.half_moves;
         let full_moves = self.full_moves;
 
-        if self.can_white_king_side_castle {
-            castling_rights.push_str("K");
-        }
-
-        if self.can_white_queen_side_castle {
-            castling_rights.push_str("Q");
-        }
-
-        if self.can_black_king_side_castle {
-            castling_rights.push_str("k");
-        }
-
-        if self.can_black_queen_side_castle {
-            castling_rights.push_str("q")
+        for (right, allowed) in self.castling_rights().iter().enumerate() {
+            if *allowed {
+                castling_rights.push(self.castling_letter(right));
+            }
         }
 
         if castling_rights.is_empty() {
-            castling_rights.push_str("-");
+            castling_rights.push('-');
         }
 
         let mut empty_square: u8 = 0;
@@ -1251,22 +920,22 @@ impl Chess {
 
                 if self.get_color(piece) == WHITE {
                     match piece_type {
-                        PAWN => fen.push_str("P"),
-                        ROOK => fen.push_str("R"),
-                        KNIGHT => fen.push_str("N"),
-                        BISHOP => fen.push_str("B"),
-                        QUEEN => fen.push_str("Q"),
-                        KING => fen.push_str("K"),
+                        PAWN => fen.push('P'),
+                        ROOK => fen.push('R'),
+                        KNIGHT => fen.push('N'),
+                        BISHOP => fen.push('B'),
+                        QUEEN => fen.push('Q'),
+                        KING => fen.push('K'),
                         _ => panic!("error generating FEN"),
                     }
                 } else {
                     match piece_type {
-                        PAWN => fen.push_str("p"),
-                        ROOK => fen.push_str("r"),
-                        KNIGHT => fen.push_str("n"),
-                        BISHOP => fen.push_str("b"),
-                        QUEEN => fen.push_str("q"),
-                        KING => fen.push_str("k"),
+                        PAWN => fen.push('p'),
+                        ROOK => fen.push('r'),
+                        KNIGHT => fen.push('n'),
+                        BISHOP => fen.push('b'),
+                        QUEEN => fen.push('q'),
+                        KING => fen.push('k'),
                         _ => panic!("error generating FEN"),
                     }
                 }
@@ -1287,7 +956,7 @@ impl Chess {
             }
         }
 
-        vec![
+        [
             fen,
             turn.to_string(),
             castling_rights,
@@ -1298,189 +967,365 @@ impl Chess {
         .join(" ")
     }
 
+    /// Load a position from a FEN string, panicking if it is malformed.
+    /// Use `try_load_fen` for positions that come from user input.
     pub fn load_fen(&mut self, fen: String) {
-        let fen_parts: Vec<&str> = fen.split(" ").collect();
+        if let Err(err) = self.try_load_fen(&fen) {
+            panic!("can't load fen \"{}\": {}", fen, err);
+        }
+    }
+
+    /// Load a position from a FEN string, replacing the current game.
+    /// If the FEN is invalid, the current game is left untouched.
+    pub fn try_load_fen(&mut self, fen: &str) -> Result<(), FenError> {
+        let fen_parts: Vec<&str> = fen.split_whitespace().collect();
+
+        // the halfmove and fullmove fields are often omitted, so only require the first four
+        if fen_parts.len() < 4 || fen_parts.len() > 6 {
+            return Err(FenError::WrongFieldCount(fen_parts.len()));
+        }
+
+        let mut chess = Chess::new();
+        chess.chess960 = self.chess960;
+
+        let ranks: Vec<&str> = fen_parts[0].split('/').collect();
+
+        if ranks.len() != 8 {
+            return Err(FenError::WrongRankCount(ranks.len()));
+        }
+
+        let mut white_kings = 0;
+        let mut black_kings = 0;
 
-        let ranks: Vec<&str> = fen_parts[0].split("/").collect();
+        for (i, rank) in ranks.iter().enumerate() {
+            let rank_number = 8 - i as u8;
+            let mut file: usize = 0;
 
-        let mut idx: usize = 0;
-        for rank in ranks {
             for piece in rank.chars() {
-                match piece {
-                    'p' => {
-                        let rank = 8 - ((BOARD_MAP[idx] >> 4) + 1) + 1;
+                if let Some(empty_squares) = piece.to_digit(10).filter(|n| (1..=8).contains(n)) {
+                    file += empty_squares as usize;
 
-                        if rank != 7 {
-                            self.set(MOVED_BLACK_PAWN, BOARD_MAP[idx])
-                        } else {
-                            self.set(BLACK_PAWN, BOARD_MAP[idx])
-                        }
-                    }
-                    'r' => self.set(BLACK_ROOK, BOARD_MAP[idx]),
-                    'n' => self.set(BLACK_KNIGHT, BOARD_MAP[idx]),
-                    'b' => self.set(BLACK_BISHOP, BOARD_MAP[idx]),
-                    'q' => self.set(BLACK_QUEEN, BOARD_MAP[idx]),
-                    'k' => self.set(BLACK_KING, BOARD_MAP[idx]),
-                    'P' => {
-                        let rank = 8 - ((BOARD_MAP[idx] >> 4) + 1) + 1;
-
-                        if rank != 2 {
-                            self.set(MOVED_PAWN, BOARD_MAP[idx])
-                        } else {
-                            self.set(PAWN, BOARD_MAP[idx])
-                        }
+                    if file > 8 {
+                        return Err(FenError::RankOverflow(rank_number));
                     }
-                    'R' => self.set(ROOK, BOARD_MAP[idx]),
-                    'N' => self.set(KNIGHT, BOARD_MAP[idx]),
-                    'B' => self.set(BISHOP, BOARD_MAP[idx]),
-                    'Q' => self.set(QUEEN, BOARD_MAP[idx]),
-                    'K' => self.set(KING, BOARD_MAP[idx]),
-                    '1'..='8' => idx += piece.to_digit(10).unwrap() as usize - 1,
-                    _ => panic!("can't load fen pieces"),
+
+                    continue;
+                }
+
+                if file >= 8 {
+                    return Err(FenError::RankOverflow(rank_number));
                 }
 
-                idx += 1;
+                let idx = BOARD_MAP[i * 8 + file];
+
+                let piece_type = match piece {
+                    // a pawn that is not on its starting rank must have moved already
+                    'p' if rank_number == 7 => BLACK_PAWN,
+                    'p' => MOVED_BLACK_PAWN,
+                    'r' => BLACK_ROOK,
+                    'n' => BLACK_KNIGHT,
+                    'b' => BLACK_BISHOP,
+                    'q' => BLACK_QUEEN,
+                    'k' => BLACK_KING,
+                    'P' if rank_number == 2 => PAWN,
+                    'P' => MOVED_PAWN,
+                    'R' => ROOK,
+                    'N' => KNIGHT,
+                    'B' => BISHOP,
+                    'Q' => QUEEN,
+                    'K' => KING,
+                    _ => return Err(FenError::InvalidPiece(piece, rank_number)),
+                };
+
+                match piece_type {
+                    KING => white_kings += 1,
+                    BLACK_KING => black_kings += 1,
+                    _ => {}
+                }
+
+                if chess.get_type(piece_type) == PAWN && (rank_number == 1 || rank_number == 8) {
+                    return Err(FenError::PawnOnBackRank(
+                        chess.convert_index_algebraic_notation(idx),
+                    ));
+                }
+
+                chess.set(piece_type, idx);
+                file += 1;
+            }
+
+            if file < 8 {
+                return Err(FenError::RankUnderflow(rank_number));
             }
         }
 
+        match (white_kings, black_kings) {
+            (0, _) => return Err(FenError::MissingKing("white")),
+            (_, 0) => return Err(FenError::MissingKing("black")),
+            (1, 1) => {}
+            (1, _) => return Err(FenError::TooManyKings("black")),
+            _ => return Err(FenError::TooManyKings("white")),
+        }
+
         // set turn
         match fen_parts[1] {
-            "w" => self.set_turn(WHITE),
-            "b" => self.set_turn(BLACK),
-            _ => panic!("can't load fen turn"),
+            "w" => chess.set_turn(Color::White),
+            "b" => chess.set_turn(Color::Black),
+            turn => return Err(FenError::InvalidTurn(turn.to_string())),
         }
 
-        // castling rights
-        for castling_right in fen_parts[2].chars() {
-            match castling_right {
-                'K' => {
-                    self.can_white_king_side_castle = true;
-                }
-                'Q' => {
-                    self.can_white_queen_side_castle = true;
-                }
-                'k' => {
-                    self.can_black_king_side_castle = true;
-                }
-                'q' => {
-                    self.can_black_queen_side_castle = true;
-                }
+        // castling rights, either `KQkq` or the files of the rooks as in Shredder-FEN, which
+        // is only used for Chess960
+        let invalid_castling = || FenError::InvalidCastlingRights(fen_parts[2].to_string());
+        let mut rights = [false; 4];
+
+        if fen_parts[2] != "-" {
+            if fen_parts[2]
+                .chars()
+                .any(|c| c.is_ascii_alphabetic() && c.to_ascii_lowercase() <= 'h')
+            {
+                chess.chess960 = true;
+            }
+
+            for letter in fen_parts[2].chars() {
+                let (color, king_idx) = if letter.is_ascii_uppercase() {
+                    (Color::White, chess.kings.white)
+                } else {
+                    (Color::Black, chess.kings.black)
+                };
+
+                let (king_side, rook_idx) = match letter.to_ascii_lowercase() {
+                    'k' => (true, chess.outermost_rook(color, true)),
+                    'q' => (false, chess.outermost_rook(color, false)),
+                    file @ 'a'..='h' => {
+                        let back_rank = king_idx & 0x70;
+                        let rook_idx = back_rank | (file as u8 - b'a');
+
+                        (rook_idx > king_idx, rook_idx)
+                    }
+                    _ => return Err(invalid_castling()),
+                };
 
-                '-' => {
-                    self.can_white_king_side_castle = false;
-                    self.can_white_queen_side_castle = false;
-                    self.can_black_king_side_castle = false;
-                    self.can_black_queen_side_castle = false;
+                let right = castling_right(color, king_side);
+
+                // each right can only be listed once
+                if rights[right] {
+                    return Err(invalid_castling());
                 }
-                _ => panic!("cant load fen castling rights"),
+
+                rights[right] = true;
+                chess.castling_rooks[right] = rook_idx;
             }
         }
 
+        chess.set_castling_rights(rights);
+
+        // rights that can't be used because the king or rook isn't on its square are dropped
+        chess.update_castling_rights();
+
         // en passant square
         let square = fen_parts[3];
 
-        match square {
-            // no en passant square
-            "-" => {
-                self.lastest_en_passant_square = None;
-            }
-            _ => {
-                self.lastest_en_passant_square = Some(square.to_string());
-                let idx = self.convert_algebraic_notation_to_index(square) as usize;
+        if square != "-" {
+            let idx = match parse_square(square) {
+                Some(idx) if !chess.is_occupied(idx) => idx,
+                _ => return Err(FenError::InvalidEnPassantSquare(square.to_string())),
+            };
+
+            // the square is behind a pawn that has just moved 2 squares
+            let rank = 8 - ((idx >> 4) + 1) + 1;
+            let expected_rank = if chess.turn == WHITE { 6 } else { 3 };
 
-                self.set(EN_PASSANT_SQUARE, BOARD_MAP[idx]);
+            if rank != expected_rank {
+                return Err(FenError::EnPassantWrongRank(square.to_string()));
             }
+
+            chess.lastest_en_passant_square = Some(square.to_string());
+            chess.set(EN_PASSANT_SQUARE, idx);
+            chess.en_passant_hash = chess.en_passant_key();
+        }
+
+        if let Some(half_moves) = fen_parts.get(4) {
+            chess.half_moves = half_moves
+                .parse()
+                .map_err(|_| FenError::InvalidHalfMoves(half_moves.to_string()))?;
         }
 
-        self.half_moves = fen_parts[4].parse().unwrap();
-        self.full_moves = fen_parts[5].parse().unwrap();
+        chess.full_moves = match fen_parts.get(5) {
+            Some(full_moves) => full_moves
+                .parse()
+                .map_err(|_| FenError::InvalidFullMoves(full_moves.to_string()))?,
+            None => 1,
+        };
+
+        chess.starting_fen = chess.get_fen();
 
-        *self
-            .unique_positions
-            .entry(fen_parts[0].to_string())
-            .or_insert(0) += 1;
+        *self = chess;
+
+        Ok(())
     }
 
     /// Return true or false if the color to move is in check
     pub fn in_check(&self) -> bool {
-        let king_idx = match self.turn {
-            WHITE => self.kings.white,
-            BLACK => self.kings.black,
-            _ => panic!("Turn cannot be determined when checking if king is attacked"),
-        };
-
-        return self.is_attacked(king_idx);
+        Position::from(self).in_check()
     }
 
     // DISREGARD
     pub fn is_checkmate(&mut self) -> bool {
-        let mut no_legal_moves = true;
+        let position = Position::from(&*self);
 
-        for idx in 0..BOARD_SIZE {
-            if !self.is_on_board(idx) {
-                continue;
-            }
+        position.in_check() && position.legal_moves().is_empty()
+    }
+    // DISREGARD
+    /// Whether the game is drawn, or a draw could be claimed right now.
+    ///
+    /// This is `outcome` ending in a draw, or `can_claim_draw` for threefold repetition and the
+    /// fifty-move rule, which stay claimable draws until `claim_draw` is called.
+    pub fn is_draw(&self) -> bool {
+        self.outcome().is_some_and(|outcome| outcome.is_draw()) || self.can_claim_draw()
+    }
+    // DISREGARD
+    /// stalemate happens when a player has no legal moves and is not in check
+    pub fn is_stalemate(&mut self) -> bool {
+        let position = Position::from(&*self);
 
-            let piece = self.get(idx);
+        !position.in_check() && position.legal_moves().is_empty()
+    }
 
-            if self.is_friendly(piece) {
-                let inner_moves = self.inner_moves(idx);
+    /// How many times the current position has occurred, counting this one.
+    ///
+    /// Only the positions since the last pawn move or capture are compared, the earlier ones
+    /// can't come back.
+    pub fn repetition_count(&self) -> usize {
+        let hash = self.hash();
 
-                if inner_moves.len() > 0 {
-                    no_legal_moves = false;
-                }
-            }
-        }
+        1 + self
+            .positions
+            .iter()
+            .rev()
+            .take(self.half_moves as usize)
+            .filter(|&&position| position == hash)
+            .count()
+    }
 
-        self.in_check() && no_legal_moves
+    // if the same position has been seen 3 times, it is threefold repetition
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 3
     }
-    // DISREGARD
-    pub fn is_draw(&mut self) -> bool {
-        self.is_stalemate()
-            || self.is_threefold_repetition()
-            || self.is_50_moves_rule()
-            || self.is_insufficient_materials()
+
+    /// The game is drawn without a claim once the same position has occurred 5 times
+    pub fn is_fivefold_repetition(&self) -> bool {
+        self.repetition_count() >= 5
     }
-    // DISREGARD
-    /// stalemate happens when a player has no legal inner_moves and is not in check
-    pub fn is_stalemate(&mut self) -> bool {
-        let mut no_legal_moves = true;
 
-        for idx in 0..BOARD_SIZE {
-            if !self.is_on_board(idx) {
-                continue;
+    /// Whether the player to move may claim a draw, by threefold repetition or the fifty-move
+    /// rule. Unlike stalemate, insufficient material or fivefold repetition, these draws only
+    /// end the game if a player asks for it, so `outcome` stays `None` until `claim_draw` while
+    /// `is_draw` is already true.
+    pub fn can_claim_draw(&self) -> bool {
+        self.is_threefold_repetition() || self.is_50_moves_rule()
+    }
+
+    /// The result of the game, or `None` while it is still going on.
+    ///
+    /// Checkmate, stalemate, insufficient material, fivefold repetition and the
+    /// seventy-five-move rule end the game by themselves. Threefold repetition and the
+    /// fifty-move rule only end it once claimed with `claim_draw`, see `can_claim_draw`.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if let Some((ply, outcome)) = self.declared_outcome {
+            if ply == self.history.len() {
+                return Some(outcome);
             }
+        }
 
-            let piece = self.get(idx);
+        let position = Position::from(self);
 
-            if (piece != EMPTY || piece != EN_PASSANT_SQUARE) && self.is_friendly(piece) {
-                let inner_moves = self.inner_moves(idx);
+        if position.legal_moves().is_empty() {
+            return Some(if position.in_check() {
+                Outcome::win(position.turn().other(), Termination::Checkmate)
+            } else {
+                Outcome::draw(Termination::Stalemate)
+            });
+        }
 
-                if inner_moves.len() > 0 {
-                    no_legal_moves = false;
-                }
-            }
+        if self.is_insufficient_materials() {
+            Some(Outcome::draw(Termination::InsufficientMaterial))
+        } else if self.is_fivefold_repetition() {
+            Some(Outcome::draw(Termination::FivefoldRepetition))
+        } else if self.is_75_moves_rule() {
+            Some(Outcome::draw(Termination::SeventyFiveMoves))
+        } else {
+            None
         }
+    }
+
+    /// Claim a draw by threefold repetition or the fifty-move rule, returns false if neither
+    /// applies
+    pub fn claim_draw(&mut self) -> bool {
+        let termination = if self.is_threefold_repetition() {
+            Termination::ThreefoldRepetition
+        } else if self.is_50_moves_rule() {
+            Termination::FiftyMoves
+        } else {
+            return false;
+        };
+
+        self.declare(Outcome::draw(termination));
 
-        !self.in_check() && no_legal_moves
+        true
     }
 
-    // convert every move to FEN, store it as a unique key in a hashmap
-    // if the value >= 3, then it is threefold repetition
-    pub fn is_threefold_repetition(&mut self) -> bool {
-        let fen = self.get_fen();
-        let position = fen.split(" ").collect::<Vec<&str>>()[0];
+    /// `color` resigns and the other player wins
+    pub fn resign(&mut self, color: Color) {
+        self.declare(Outcome::win(color.other(), Termination::Resignation));
+    }
 
-        if let Some(count) = self.unique_positions.get(position) {
-            *count >= 3
-        } else {
-            false
+    /// `color` ran out of time and the other player wins
+    pub fn lose_on_time(&mut self, color: Color) {
+        self.declare(Outcome::win(color.other(), Termination::Timeout));
+    }
+
+    pub fn agree_draw(&mut self) {
+        self.declare(Outcome::draw(Termination::Agreement));
+    }
+
+    /// Record an outcome unless the game is already over
+    fn declare(&mut self, outcome: Outcome) {
+        if self.outcome().is_none() {
+            self.declared_outcome = Some((self.history.len(), outcome));
+        }
+    }
+
+    /// The Zobrist key of the position.
+    ///
+    /// Positions are the same under the FIDE rules if the same pieces are on the same squares,
+    /// with the same player to move, the same castling rights and the same possible moves.
+    /// The en passant square only counts if the pawn can actually be captured.
+    pub fn hash(&self) -> u64 {
+        let mut hash = self.pieces_hash;
+
+        if self.turn == BLACK {
+            hash ^= zobrist::black_to_move();
+        }
+
+        for (right, allowed) in self.castling_rights().iter().enumerate() {
+            if *allowed {
+                hash ^= zobrist::castling(right);
+            }
         }
+
+        hash ^ self.en_passant_hash
     }
+
     // DISREGARD
-    pub fn is_50_moves_rule(&mut self) -> bool {
+    pub fn is_50_moves_rule(&self) -> bool {
         self.half_moves >= 100
     }
+
+    /// After 75 moves by each player without a pawn move or capture the game is drawn
+    /// without a claim, unless the last move was checkmate
+    pub fn is_75_moves_rule(&self) -> bool {
+        self.half_moves >= 150
+    }
     // DISREGARD
     /*
         If both sides have any one of the following, and there are no pawns or other pieces on the board:
@@ -1493,7 +1338,7 @@ impl Chess {
 
         accoring to https://support.chess.com/article/128-what-does-insufficient-mating-material-mean
     */
-    pub fn is_insufficient_materials(&mut self) -> bool {
+    pub fn is_insufficient_materials(&self) -> bool {
         let mut friendly_knights = 0;
         let mut friendly_bishops = 0;
         let mut friendly_dark_bishops = 0;
@@ -1518,8 +1363,9 @@ impl Chess {
             // if rank is even and file is odd = light
             // if rank is odd and file is even = light
 
-            if piece != EMPTY {
-                let piece_without_color = self.remove_color(piece);
+            if piece != EMPTY && piece != EN_PASSANT_SQUARE {
+                // pawns and rooks that have moved still count as mating material
+                let piece_without_color = self.remove_mask(self.remove_color(piece), MOVED_MASK);
 
                 if piece_without_color == PAWN
                     || piece_without_color == ROOK
@@ -1610,104 +1456,36 @@ impl Chess {
             return true;
         }
 
-        return false;
+        false
     }
 
-    pub fn is_attacked(&self, square_idx: PieceIndex) -> bool {
-        let mut is_attacked = false;
-        for idx in 0..BOARD_SIZE {
-            if !self.is_on_board(idx) {
-                continue;
-            }
-
-            if is_attacked {
-                break;
-            }
-
-            let piece = self.get(idx);
-            let attacker_idx = idx;
-            let defender_idx = square_idx;
-            let defender_piece = self.get(defender_idx);
-
-            if (self.is_friendly(piece) && piece != EN_PASSANT_SQUARE)
-                || piece == EMPTY
-                || piece == EN_PASSANT_SQUARE
-            {
-                continue;
-            }
-
-            let diff = (defender_idx as i16 - attacker_idx as i16) + 119;
-
-            let attack_bits_mask = ATTACKS[diff as usize];
-
-            if attack_bits_mask != 0 {
-                // although the king can be attacked from a particular square, we also
-                // have to take into account if that piece can attack from there
-                let piece = self.get(attacker_idx);
-
-                // remove the color mask
-                let piece_type = self.get_type(piece);
+    pub fn half_moves(&self) -> u16 {
+        self.half_moves
+    }
 
-                // check if that piece can attack from that particular square
-                if (piece_type & attack_bits_mask) == piece_type {
-                    if piece_type == KNIGHT {
-                        return true;
-                    } else if piece_type == PAWN {
-                        let piece = self.remove_mask(piece, MOVED_MASK);
-                        is_attacked = (piece & attack_bits_mask == piece)
-                            && self.get_color(attack_bits_mask) == self.get_color(piece);
-                    } else {
-                        // check if there is a piece standing in the way of the attack
-                        let delta = DELTAS[diff as usize];
-
-                        let mut destination_idx = attacker_idx as i16 + delta as i16;
-
-                        while self.is_on_board(destination_idx as PieceIndex) {
-                            let piece = self.get(destination_idx as PieceIndex);
-                            // 11000001
-                            if piece == defender_piece
-                                && destination_idx as PieceIndex == defender_idx
-                            {
-                                is_attacked = true;
-                                break;
-                            } else {
-                                // check if there is a piece blocking the attack
-                                if self.is_occupied(destination_idx as u8) {
-                                    break;
-                                }
-                            }
+    pub fn full_moves(&self) -> u16 {
+        self.full_moves
+    }
 
-                            destination_idx += delta as i16;
-                        }
-                    }
-                }
-            }
-        }
-        return is_attacked;
+    pub(crate) fn en_passant_square(&self) -> Option<PieceIndex> {
+        self.lastest_en_passant_square
+            .as_deref()
+            .and_then(parse_square)
     }
 
-    pub fn turn(&self) -> char {
-        match self.turn {
-            WHITE => 'w',
-            BLACK => 'b',
-            _ => panic!("Unknown turn"),
-        }
+    pub fn turn(&self) -> Color {
+        Color::from_mask(self.turn)
     }
 
-    /// 0 = white, 128 = black
-    pub fn set_turn(&mut self, turn: u8) {
-        if turn == WHITE {
-            self.turn = WHITE;
-        } else {
-            self.turn = BLACK;
-        }
+    pub fn set_turn(&mut self, turn: Color) {
+        self.turn = turn.mask();
     }
 
     pub fn clear(&mut self) {
         *self = Self::new();
     }
 
-    pub fn convert_algebraic_notation_to_index(&self, notation: &str) -> u8 {
+    fn convert_algebraic_notation_to_index(&self, notation: &str) -> u8 {
         let mut parts = notation.chars();
 
         let file = parts.next().unwrap();
@@ -1720,95 +1498,245 @@ impl Chess {
 
         // we minus rank from 7 because the board is reversed (upside down)
         // so for example, for "e7", the rank 7 is rank 1 on our board
-        (8 * (7 - rank) + file) as u8
+        8 * (7 - rank) + file
     }
 
-    pub fn convert_index_algebraic_notation(&self, index: u8) -> String {
-        let file = index & 7;
-        let rank = 8 - ((index >> 4) + 1) + 1;
+    pub(crate) fn convert_index_algebraic_notation(&self, index: u8) -> String {
+        square_name(index)
+    }
 
-        let file_letter = FILES[file as usize];
+    /// Count the leaf nodes `depth` plies below the position, see `perft::Perft::divide` for
+    /// the count of each move
+    // https://www.chessprogramming.org/Perft
+    pub fn perft(&mut self, depth: u8) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
 
-        let mut notation = String::new();
+        let mut nodes: u64 = 0;
 
-        notation.push_str(file_letter);
-        notation.push_str(rank.to_string().as_str());
+        for _move in self.generate_legal_moves() {
+            self.play_move(_move);
+            nodes += self.perft(depth - 1);
+            self.undo();
+        }
 
-        notation
+        nodes
     }
 
-    // https://www.chessprogramming.org/Perft
-    pub fn perft(&mut self, depth: u8, yah: bool) -> u64 {
-        let mut nodes: u64 = 0;
-        let mut cnt = 0;
+    pub fn generate_legal_moves(&mut self) -> Vec<Move> {
+        Position::from(&*self).legal_moves()
+    }
 
-        if depth <= 0 {
-            cnt = 1;
-            return 1;
+    /// Play a move returned by `generate_legal_moves`
+    pub fn make_move(&mut self, _move: Move) -> Result<(), MoveError> {
+        if !self.is_on_board(_move.from)
+            || !self.is_occupied(_move.from)
+            || !self.is_friendly(self.get(_move.from))
+            || !self.legal_moves_from(_move.from).contains(&_move)
+        {
+            return Err(MoveError::IllegalMove);
         }
 
-        let moves = self.generate_legal_moves();
+        self.play_move(_move);
+
+        Ok(())
+    }
 
-        for _move in moves {
-            self.inner_move_piece(_move.from, _move.to);
+    /// Find the legal move written in the long algebraic notation used by UCI engines
+    pub fn parse_uci(&mut self, uci: &str) -> Result<Move, MoveError> {
+        let invalid = || MoveError::InvalidNotation(uci.to_string());
 
-            let from = self.convert_index_algebraic_notation(_move.from);
-            let to = self.convert_index_algebraic_notation(_move.to);
+        if !uci.is_ascii() || uci.len() < 4 || uci.len() > 5 {
+            return Err(invalid());
+        }
 
-            self.change_turn();
-            self.update_castling_rights();
+        let from = parse_square(&uci[0..2]).ok_or_else(invalid)?;
+        let to = parse_square(&uci[2..4]).ok_or_else(invalid)?;
 
-            if self.in_check() {
-                self.checks += 1;
-            }
+        let promotion = match &uci[4..] {
+            "" => None,
+            "q" => Some(QUEEN),
+            "r" => Some(ROOK),
+            "b" => Some(BISHOP),
+            "n" => Some(KNIGHT),
+            _ => return Err(invalid()),
+        };
+
+        if !self.is_occupied(from) || !self.is_friendly(self.get(from)) {
+            return Err(MoveError::IllegalMove);
+        }
+
+        let moves: Vec<Move> = self
+            .legal_moves_from(from)
+            .into_iter()
+            .filter(|m| m.to == to)
+            .collect();
+
+        if moves.is_empty() {
+            return Err(MoveError::IllegalMove);
+        }
+
+        moves
+            .into_iter()
+            .find(|m| m.promotion.map(|p| self.get_type(p)) == promotion)
+            .ok_or(MoveError::InvalidPromotion)
+    }
+
+    /// Play a move written in the long algebraic notation used by UCI engines, e.g. `e2e4`,
+    /// `e1g1` or `e7e8q`, and return it
+    pub fn make_uci_move(&mut self, uci: &str) -> Result<Move, MoveError> {
+        let _move = self.parse_uci(uci)?;
+
+        self.play_move(_move);
+
+        Ok(_move)
+    }
+
+    /// The FEN of the position the game was loaded from
+    pub fn starting_fen(&self) -> &str {
+        &self.starting_fen
+    }
+
+    /// The moves played since the position was loaded, oldest first
+    pub fn history(&self) -> Vec<Move> {
+        self.history
+            .iter()
+            .map(|entry| {
+                let piece = self.remove_mask(entry.from_piece, MOVED_MASK);
+                let mut flags = MoveFlag::NORMAL;
+                let mut captured = None;
+                let mut promotion = None;
+
+                if entry.en_passant_capture {
+                    captured = Some(PAWN | (entry.turn ^ BLACK));
+                    flags |= MoveFlag::CAPTURE | MoveFlag::EN_PASSANT;
+                } else if entry.capture {
+                    captured = Some(self.remove_mask(entry.to_piece, MOVED_MASK));
+                    flags |= MoveFlag::CAPTURE;
+                }
+
+                if entry.en_passant_move {
+                    flags |= MoveFlag::BIG_PAWN;
+                }
+
+                if entry.promotion {
+                    flags |= MoveFlag::PROMOTION;
+                    promotion = Some(entry.promoted_to.unwrap_or(QUEEN | entry.turn));
+                }
+
+                if let Some((_, rook_to)) = entry.castle {
+                    // the rook ends up on the f or d file
+                    flags |= if rook_to & 7 == 5 {
+                        MoveFlag::KING_SIDE_CASTLE
+                    } else {
+                        MoveFlag::QUEEN_SIDE_CASTLE
+                    };
+                }
+
+                Move {
+                    from: entry.from_idx,
+                    to: entry.to_idx,
+                    piece,
+                    captured,
+                    promotion,
+                    flags,
+                }
+            })
+            .collect()
+    }
 
-            cnt = self.perft(depth - 1, false);
-            nodes += cnt;
+    /// Play a move that is known to be legal and pass the turn to the other player
+    pub(crate) fn play_move(&mut self, _move: Move) {
+        self.inner_move_piece(_move.from, _move.to);
 
-            if yah {
-                println!("{} {}", format!("{}{}", from, to), cnt);
+        if let Some(promotion) = _move.promotion {
+            self.set(promotion, _move.to);
+            self.promotions += 1;
 
-                *self.moves.entry(format!("{}{}", from, to)).or_insert(0) = cnt;
+            if let Some(entry) = self.history.last_mut() {
+                entry.promoted_to = Some(promotion);
             }
+        }
 
-            self.undo()
+        if self.turn == BLACK {
+            self.full_moves = self.full_moves.saturating_add(1);
         }
 
-        return nodes;
+        self.update_castling_rights();
+        self.change_turn();
+
+        if self
+            .history
+            .last()
+            .is_some_and(|entry| entry.en_passant_move)
+        {
+            self.en_passant_hash = self.en_passant_key();
+        }
     }
 
-    pub fn generate_legal_moves(&mut self) -> Vec<Move> {
+    fn legal_moves_from(&mut self, square_idx: PieceIndex) -> Vec<Move> {
+        let piece = self.remove_mask(self.get(square_idx), MOVED_MASK);
+        let piece_type = self.get_type(piece);
+
         let mut moves = vec![];
 
-        for idx in 0..BOARD_SIZE {
-            if !self.is_on_board(idx) {
-                continue;
-            }
+        for to_idx in self.inner_moves(square_idx) {
+            let to_piece = self.get(to_idx);
 
-            let piece = self.get(idx);
-            // let piece_type = self.get_type(piece);
+            let mut _move = Move {
+                from: square_idx,
+                to: to_idx,
+                piece,
+                captured: None,
+                promotion: None,
+                flags: MoveFlag::NORMAL,
+            };
 
-            if piece == EN_PASSANT_SQUARE {
-                continue;
+            if self.is_occupied(to_idx) {
+                _move.captured = Some(self.remove_mask(to_piece, MOVED_MASK));
+                _move.flags |= MoveFlag::CAPTURE;
             }
 
-            if self.is_occupied(idx) && self.is_friendly(piece) {
-                // for _move in self.moves(&self.convert_index_algebraic_notation(idx)) {
-                //     // if _move.is_empty() {
-                //     //     continue;
-                //     // }
-                //     moves.push(_move);
-                // }
-                for _move in self.inner_moves(idx) {
-                    if _move > 150 {
-                        continue;
+            if piece_type == PAWN {
+                if to_piece == EN_PASSANT_SQUARE && to_idx.abs_diff(square_idx) != 16 {
+                    _move.captured = Some(PAWN | (self.turn ^ BLACK));
+                    _move.flags |= MoveFlag::CAPTURE | MoveFlag::EN_PASSANT;
+                }
+
+                if to_idx.abs_diff(square_idx) == 32 {
+                    _move.flags |= MoveFlag::BIG_PAWN;
+                }
+
+                let rank = 8 - ((to_idx >> 4) + 1) + 1;
+
+                if rank == 1 || rank == 8 {
+                    _move.flags |= MoveFlag::PROMOTION;
+
+                    for promotion in [QUEEN, ROOK, BISHOP, KNIGHT] {
+                        moves.push(Move {
+                            promotion: Some(promotion | self.turn),
+                            .._move
+                        });
                     }
-                    moves.push(Move {
-                        from: idx,
-                        to: _move,
-                    })
+
+                    continue;
+                }
+            }
+
+            if piece_type == KING {
+                // in Chess960 the king takes its own rook, which is not a capture
+                if let Some(right) = self.castling_right(square_idx, to_idx) {
+                    _move.captured = None;
+                    _move.flags = if right.is_multiple_of(2) {
+                        MoveFlag::KING_SIDE_CASTLE
+                    } else {
+                        MoveFlag::QUEEN_SIDE_CASTLE
+                    };
                 }
             }
+
+            moves.push(_move);
         }
 
         moves
@@ -1818,13 +1746,13 @@ impl Chess {
         self.half_moves = 0;
     }
 
-    pub fn change_turn(&mut self) {
+    fn change_turn(&mut self) {
         if self.turn == WHITE {
             self.last_turn = WHITE;
-            self.set_turn(BLACK);
+            self.set_turn(Color::Black);
         } else {
             self.last_turn = BLACK;
-            self.set_turn(WHITE);
+            self.set_turn(Color::White);
         }
     }
 
@@ -1834,6 +1762,17 @@ impl Chess {
         piece != EMPTY && piece != EN_PASSANT_SQUARE
     }
 
+    /// The Zobrist key of the en passant square if a pawn of the side to move can take en
+    /// passant without leaving its king in check, 0 otherwise
+    fn en_passant_key(&self) -> u64 {
+        match self.en_passant_square() {
+            Some(idx) if Position::from(self).can_capture_en_passant() => {
+                zobrist::en_passant(idx & 7)
+            }
+            _ => 0,
+        }
+    }
+
     fn clear_latest_en_passant_square(&mut self) {
         if let Some(sq) = &self.lastest_en_passant_square {
             let idx = BOARD_MAP[self.convert_algebraic_notation_to_index(sq.as_str()) as usize];
@@ -1859,16 +1798,6 @@ impl Chess {
         self.remove_mask(piece, COLOR_MASK)
     }
 
-    /// (white kingside, white queenside, blac kingside, black queenside)
-    pub fn get_castling_rights_tests(&self) -> (bool, bool, bool, bool) {
-        (
-            self.can_white_king_side_castle,
-            self.can_white_queen_side_castle,
-            self.can_black_king_side_castle,
-            self.can_black_queen_side_castle,
-        )
-    }
-
     pub fn get_castling_rights(&self) -> (bool, bool) {
         if self.turn == WHITE {
             (
@@ -1883,51 +1812,134 @@ impl Chess {
         }
     }
 
+    /// Drop the castling rights whose king or rook has moved or been captured
     pub fn update_castling_rights(&mut self) {
-        if self.kings.white != 116 {
-            self.can_white_king_side_castle = false;
-            self.can_white_queen_side_castle = false;
-        }
+        let mut rights = self.castling_rights();
 
-        if self.kings.black != 4 {
-            self.can_black_king_side_castle = false;
-            self.can_black_queen_side_castle = false;
-        }
-        // 119 = h1
-        // 112 = a1
-        let right_white_rook_idx = 119;
-        let left_white_rook_idx = 112;
+        for (right, allowed) in rights.iter_mut().enumerate() {
+            let (color, king_idx) = if right < 2 {
+                (WHITE, self.kings.white)
+            } else {
+                (BLACK, self.kings.black)
+            };
 
-        // 7 = h8
-        // 0 = a8
-        let right_black_rook_idx = 7;
-        let left_black_rook_idx = 0;
+            let back_rank = if color == WHITE { 0x70 } else { 0 };
+            let rook_idx = self.castling_rooks[right];
+            let king_side = right.is_multiple_of(2);
 
-        // if it isn't an *unmoved* rook, then we can't castle
+            // both must still be on their back rank without having moved, with the rook on the
+            // side it castles to. Outside of Chess960 the king starts on the e-file.
+            let can_castle = self.get(king_idx) == KING | color
+                && king_idx & 0x70 == back_rank
+                && (self.chess960 || king_idx & 7 == 4)
+                && self.get(rook_idx) == ROOK | color
+                && rook_idx & 0x70 == back_rank
+                && (rook_idx > king_idx) == king_side;
 
-        if self.get(right_white_rook_idx) != ROOK {
-            self.can_white_king_side_castle = false;
+            *allowed &= can_castle;
         }
 
-        if self.get(left_white_rook_idx) != ROOK {
-            self.can_white_queen_side_castle = false;
-        }
+        self.set_castling_rights(rights);
+    }
 
-        if self.get(right_black_rook_idx) != BLACK_ROOK {
-            self.can_black_king_side_castle = false;
-        }
+    /// The castling rights in the order of `castling_rooks`: white kingside, white queenside,
+    /// black kingside, black queenside
+    pub fn castling_rights(&self) -> [bool; 4] {
+        [
+            self.can_white_king_side_castle,
+            self.can_white_queen_side_castle,
+            self.can_black_king_side_castle,
+            self.can_black_queen_side_castle,
+        ]
+    }
+
+    fn set_castling_rights(&mut self, rights: [bool; 4]) {
+        [
+            self.can_white_king_side_castle,
+            self.can_white_queen_side_castle,
+            self.can_black_king_side_castle,
+            self.can_black_queen_side_castle,
+        ] = rights;
+    }
 
-        if self.get(left_black_rook_idx) != BLACK_ROOK {
-            self.can_black_queen_side_castle = false;
+    /// The index in `castling_rooks` of the right a king move castles with, if it is castling.
+    /// The king takes its own rook in Chess960 and moves 2 squares in standard chess.
+    fn castling_right(&self, from_idx: PieceIndex, to_idx: PieceIndex) -> Option<usize> {
+        if self.get(from_idx) != KING | self.turn {
+            return None;
         }
+
+        let rights = self.castling_rights();
+
+        [true, false]
+            .iter()
+            .map(|&king_side| castling_right(self.turn(), king_side))
+            .find(|&right| {
+                let target = if self.chess960 {
+                    self.castling_rooks[right]
+                } else {
+                    castled_squares(from_idx, right).0
+                };
+
+                rights[right] && to_idx == target
+            })
     }
 
-    fn is_queen_side_castling(&self, from: PieceIndex, to: PieceIndex) -> bool {
-        to as i8 - from as i8 == -2
+    /// The rook of `color` furthest from its king on one side, which `K` and `Q` refer to in
+    /// X-FEN. Outside of Chess960, or without such a rook, it is the corner square.
+    fn outermost_rook(&self, color: Color, king_side: bool) -> PieceIndex {
+        let king_idx = match color {
+            Color::White => self.kings.white,
+            Color::Black => self.kings.black,
+        };
+
+        let back_rank = if color == Color::White { 0x70 } else { 0 };
+        let corner = if king_side { back_rank | 7 } else { back_rank };
+
+        if !self.chess960 || king_idx & 0x70 != back_rank {
+            return corner;
+        }
+
+        // a rook that has moved still counts, as FEN doesn't say which pieces have moved
+        let is_rook = |file: &u8| {
+            self.remove_mask(self.get(back_rank | file), MOVED_MASK) == ROOK | color.mask()
+        };
+        let mut files = (0..8).filter(|&file| (file > king_idx & 7) == king_side);
+
+        let outermost = if king_side {
+            files.rfind(is_rook)
+        } else {
+            files.find(is_rook)
+        };
+
+        outermost.map_or(corner, |file| back_rank | file)
     }
 
-    fn is_king_side_castling(&self, from: PieceIndex, to: PieceIndex) -> bool {
-        to as i8 - from as i8 == 2
+    /// The letter of a castling right in X-FEN: `KQkq` for the outermost rook as in standard
+    /// chess, or the file of the rook in Chess960 if another rook is further out
+    fn castling_letter(&self, right: usize) -> char {
+        let king_side = right.is_multiple_of(2);
+        let color = if right < 2 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let rook_idx = self.castling_rooks[right];
+
+        let letter = if rook_idx == self.outermost_rook(color, king_side) {
+            if king_side {
+                'K'
+            } else {
+                'Q'
+            }
+        } else {
+            (b'A' + (rook_idx & 7)) as char
+        };
+
+        match color {
+            Color::White => letter,
+            Color::Black => letter.to_ascii_lowercase(),
+        }
     }
 
     fn update_kings_position(&mut self, new_idx: PieceIndex) {
@@ -1990,7 +2002,6 @@ mod bishop {
         // // assert!(inner_moves.iter().eq(correct_moves.iter()));
 
         // chess.clear();
-        // chess.set_turn(BLACK);
 
         // chess.set(BISHOP, 103);
         // chess.set(KING | BLACK, 81);
@@ -2001,7 +2012,6 @@ mod bishop {
         // assert!(inner_moves.iter().eq(correct_moves.iter()));
 
         // chess.clear();
-        // chess.set_turn(BLACK);
 
         // chess.set(BISHOP, 103);
         // chess.set(KING | BLACK, 81);
@@ -2012,7 +2022,6 @@ mod bishop {
         // assert!(inner_moves.iter().eq(correct_moves.iter()));
 
         // chess.clear();
-        // chess.set_turn(BLACK);
 
         // chess.set(BISHOP, 0);
         // chess.set(KING | BLACK, 7);
@@ -2057,7 +2066,6 @@ mod bishop {
         // chess.clear();
 
         // //======= BLACK =======
-        // chess.set_turn(BLACK);
 
         // chess.set(BISHOP, 0);
         // chess.set(KING | BLACK, 17);
@@ -2067,7 +2075,6 @@ mod bishop {
         // assert!(inner_moves.len() == 0);
 
         // chess.clear();
-        // chess.set_turn(BLACK);
 
         // chess.set(BISHOP, 67);
         // chess.set(KING | BLACK, 112);
@@ -2077,7 +2084,6 @@ mod bishop {
         // assert!(inner_moves.len() == 0);
 
         // chess.clear();
-        // chess.set_turn(BLACK);
 
         // chess.set(BISHOP, 118);
         // chess.set(KING | BLACK, 16);
@@ -2095,7 +2101,7 @@ mod bishop {
         chess.set(KING, 0);
         chess.set(BISHOP | BLACK, 17);
 
-        let inner_moves = chess.inner_moves(2 as u8);
+        let inner_moves = chess.inner_moves(2);
         let correct_moves = [17];
 
         assert!(inner_moves.iter().eq(correct_moves.iter()));
@@ -2106,7 +2112,7 @@ mod bishop {
         chess.set(KING, 37);
         chess.set(BISHOP | BLACK, 54);
 
-        let inner_moves = chess.inner_moves(99 as u8);
+        let inner_moves = chess.inner_moves(99);
         let correct_moves = [54];
 
         assert!(inner_moves.iter().eq(correct_moves.iter()));
@@ -2114,7 +2120,7 @@ mod bishop {
         // ====== BLACK =====
 
         chess.clear();
-        chess.set_turn(BLACK);
+        chess.set_turn(Color::Black);
 
         chess.set(BISHOP, 32);
         chess.set(KING | BLACK, 66);
@@ -2126,7 +2132,7 @@ mod bishop {
         assert!(inner_moves.iter().eq(correct_moves.iter()));
 
         chess.clear();
-        chess.set_turn(BLACK);
+        chess.set_turn(Color::Black);
 
         chess.set(BISHOP, 67);
         chess.set(KING | BLACK, 7);
@@ -2177,7 +2183,7 @@ mod bishop {
 
         // ==== BLACK ====
         chess.clear();
-        chess.set_turn(BLACK);
+        chess.set_turn(Color::Black);
 
         chess.set(BISHOP, 64);
         chess.set(KING | BLACK, 4);
@@ -2190,7 +2196,7 @@ mod bishop {
         assert!(inner_moves.iter().eq(correct_moves.iter()));
 
         chess.clear();
-        chess.set_turn(BLACK);
+        chess.set_turn(Color::Black);
 
         chess.set(BISHOP, 53);
         chess.set(KING | BLACK, 87);
@@ -2212,7 +2218,7 @@ mod pawn {
     fn pawn_valid_moves() {
         let mut chess = Chess::new();
 
-        chess.set_turn(BLACK);
+        chess.set_turn(Color::Black);
         chess.set(Piece::BLACK_PAWN, 98);
         let inner_moves = chess.inner_moves(98);
         let correct_moves = [114];
@@ -2221,7 +2227,7 @@ mod pawn {
 
         chess.clear();
 
-        chess.set_turn(BLACK);
+        chess.set_turn(Color::Black);
         chess.set(Piece::BLACK_PAWN, 2);
         let inner_moves = chess.inner_moves(2);
         let correct_moves = [18, 34];
@@ -2243,6 +2249,55 @@ mod pawn {
     }
 }
 
+#[cfg(test)]
+mod board {
+    use super::*;
+
+    #[test]
+    fn default_board_test() {
+        let mut chess = Chess::new();
+
+        chess.load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
+
+        #[rustfmt::skip]
+        assert_eq!(
+            [
+                130, 132, 136, 160, 144, 136, 132, 130, 0, 0, 0, 0, 0, 0, 0, 0, 
+                129, 129, 129, 129, 129, 129, 129, 129, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  0,   0,   0,   0,   0,   0,   0,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  0,   0,   0,   0,   0,   0,   0,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  0,   0,   0,   0,   0,   0,   0,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  0,   0,   0,   0,   0,   0,   0,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  1,   1,   1,   1,   1,   1,   1,   1, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  2,   4,   8,  32,  16,   8,   4,   2, 0, 0, 0, 0, 0, 0, 0, 0
+            ],
+            chess.board
+        );
+    }
+
+    #[test]
+    fn checkmate_board_test() {
+        let mut chess = Chess::new();
+
+        chess.load_fen("r3k2r/ppp2p1p/2n1p1p1/8/2B2P1q/2NPb1n1/PP4PP/R2Q3K w kq - 0 8".to_string());
+
+        #[rustfmt::skip]
+        assert_eq!(
+            [
+                130,   0,   0,   0, 144,   0,   0, 130, 0, 0, 0, 0, 0, 0, 0, 0, 
+                129, 129, 129,   0,   0, 129,   0, 129, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  0,   0, 132,   0, 193,   0, 193,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  0,   0,   0,   0,   0,   0,   0,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  0,   0,   8,   0,   0,  65,   0, 160, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  0,   0,   4,  65, 136,   0, 132,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  1,   1,   0,   0,   0,   0,   1,   1, 0, 0, 0, 0, 0, 0, 0, 0, 
+                  2,   0,   0,  32,   0,   0,   0,  16, 0, 0, 0, 0, 0, 0, 0, 0
+            ],
+            chess.board
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -0,0 +1,223 @@
+//! Bitboards and the attack tables used by the move generator
+
+use super::piece::Color;
+use super::square::Square;
+
+/// A set of squares, bit `n` is set if square `n` is in the set
+pub type Bitboard = u64;
+
+pub const EMPTY: Bitboard = 0;
+pub const RANK_1: Bitboard = 0xff;
+pub const RANK_8: Bitboard = RANK_1 << 56;
+
+pub const fn bit(square: Square) -> Bitboard {
+    1 << square.index()
+}
+
+/// Iterate over the squares of a bitboard, from a1 to h8
+pub fn squares(mut bitboard: Bitboard) -> impl Iterator<Item = Square> {
+    std::iter::from_fn(move || {
+        if bitboard == 0 {
+            return None;
+        }
+
+        let square = Square::new_unchecked(bitboard.trailing_zeros() as u8);
+        bitboard &= bitboard - 1;
+
+        Some(square)
+    })
+}
+
+/// File and rank steps of the eight ray directions. The first four go towards h8, so the
+/// nearest blocker on them is the lowest bit, the last four go towards a1.
+const DIRECTIONS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 0),
+    (1, 1),
+    (-1, 1),
+    (0, -1),
+    (-1, 0),
+    (-1, -1),
+    (1, -1),
+];
+
+const ROOK_DIRECTIONS: [usize; 4] = [0, 1, 4, 5];
+const BISHOP_DIRECTIONS: [usize; 4] = [2, 3, 6, 7];
+
+const fn step_attacks(square: usize, steps: &[(i8, i8)]) -> Bitboard {
+    let file = (square & 7) as i8;
+    let rank = (square >> 3) as i8;
+    let mut attacks = 0;
+    let mut i = 0;
+
+    while i < steps.len() {
+        let (f, r) = (file + steps[i].0, rank + steps[i].1);
+
+        if f >= 0 && f < 8 && r >= 0 && r < 8 {
+            attacks |= 1 << (r * 8 + f);
+        }
+
+        i += 1;
+    }
+
+    attacks
+}
+
+const fn step_table(steps: &[(i8, i8)]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+
+    while square < 64 {
+        table[square] = step_attacks(square, steps);
+        square += 1;
+    }
+
+    table
+}
+
+const fn ray_table() -> [[Bitboard; 64]; 8] {
+    let mut table = [[0; 64]; 8];
+    let mut direction = 0;
+
+    while direction < 8 {
+        let mut square = 0;
+
+        while square < 64 {
+            let (df, dr) = DIRECTIONS[direction];
+            let mut f = (square & 7) as i8 + df;
+            let mut r = (square >> 3) as i8 + dr;
+
+            while f >= 0 && f < 8 && r >= 0 && r < 8 {
+                table[direction][square] |= 1 << (r * 8 + f);
+                f += df;
+                r += dr;
+            }
+
+            square += 1;
+        }
+
+        direction += 1;
+    }
+
+    table
+}
+
+static KNIGHT_ATTACKS: [Bitboard; 64] = step_table(&[
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+]);
+
+static KING_ATTACKS: [Bitboard; 64] = step_table(&[
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+]);
+
+/// squares attacked by a white pawn, then by a black pawn
+static PAWN_ATTACKS: [[Bitboard; 64]; 2] = [
+    step_table(&[(-1, 1), (1, 1)]),
+    step_table(&[(-1, -1), (1, -1)]),
+];
+
+static RAYS: [[Bitboard; 64]; 8] = ray_table();
+
+/// For every pair of squares on the same line, the squares between them and the whole line
+/// through them. Both are empty for squares that don't share a line.
+const fn line_tables() -> ([[Bitboard; 64]; 64], [[Bitboard; 64]; 64]) {
+    let mut between = [[0; 64]; 64];
+    let mut line = [[0; 64]; 64];
+    let mut a = 0;
+
+    while a < 64 {
+        let mut direction = 0;
+
+        while direction < 8 {
+            let mut b = 0;
+
+            while b < 64 {
+                if RAYS[direction][a] & (1 << b) != 0 {
+                    between[a][b] = RAYS[direction][a] ^ RAYS[direction][b] ^ (1 << b);
+                    line[a][b] = RAYS[direction][a] | RAYS[(direction + 4) % 8][a] | (1 << a);
+                }
+
+                b += 1;
+            }
+
+            direction += 1;
+        }
+
+        a += 1;
+    }
+
+    (between, line)
+}
+
+static LINES: ([[Bitboard; 64]; 64], [[Bitboard; 64]; 64]) = line_tables();
+
+/// The squares strictly between two squares on the same rank, file or diagonal
+pub fn between(a: Square, b: Square) -> Bitboard {
+    LINES.0[a.index()][b.index()]
+}
+
+/// The whole rank, file or diagonal through two squares
+pub fn line(a: Square, b: Square) -> Bitboard {
+    LINES.1[a.index()][b.index()]
+}
+
+pub fn knight_attacks(square: Square) -> Bitboard {
+    KNIGHT_ATTACKS[square.index()]
+}
+
+pub fn king_attacks(square: Square) -> Bitboard {
+    KING_ATTACKS[square.index()]
+}
+
+/// The squares a pawn of `color` attacks
+pub fn pawn_attacks(color: Color, square: Square) -> Bitboard {
+    PAWN_ATTACKS[color as usize][square.index()]
+}
+
+/// The squares seen along a ray, up to and including the first occupied square
+fn ray_attacks(direction: usize, square: Square, occupied: Bitboard) -> Bitboard {
+    let ray = RAYS[direction][square.index()];
+    let blockers = ray & occupied;
+
+    if blockers == 0 {
+        return ray;
+    }
+
+    let blocker = if direction < 4 {
+        blockers.trailing_zeros()
+    } else {
+        63 - blockers.leading_zeros()
+    };
+
+    ray ^ RAYS[direction][blocker as usize]
+}
+
+pub fn rook_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    ROOK_DIRECTIONS
+        .iter()
+        .fold(0, |attacks, &d| attacks | ray_attacks(d, square, occupied))
+}
+
+pub fn bishop_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    BISHOP_DIRECTIONS
+        .iter()
+        .fold(0, |attacks, &d| attacks | ray_attacks(d, square, occupied))
+}
+
+pub fn queen_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    rook_attacks(square, occupied) | bishop_attacks(square, occupied)
+}
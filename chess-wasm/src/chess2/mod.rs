@@ -0,0 +1,11 @@
+//! Bitboard move generation, and the square and piece types of the public API
+
+pub mod board;
+pub mod piece;
+pub mod position;
+pub mod square;
+pub mod zobrist;
+
+pub use piece::{Color, Piece, Role};
+pub use position::Position;
+pub use square::Square;
@@ -0,0 +1,209 @@
+use crate::chess::Piece::*;
+use crate::chess::{BLACK, WHITE};
+use crate::errors::ParseError;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub fn other(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+
+    /// The color bit used by `Chess`, `WHITE` or `BLACK`
+    pub fn mask(self) -> u8 {
+        match self {
+            Color::White => WHITE,
+            Color::Black => BLACK,
+        }
+    }
+
+    /// The color of a piece or turn as stored by `Chess`
+    pub fn from_mask(mask: u8) -> Color {
+        if mask & BLACK == 0 {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
+    /// `w` or `b`, as in FEN
+    pub fn char(self) -> char {
+        match self {
+            Color::White => 'w',
+            Color::Black => 'b',
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.char())
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseError;
+
+    /// Parse `w`, `b`, `white` or `black`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "w" | "white" => Ok(Color::White),
+            "b" | "black" => Ok(Color::Black),
+            _ => Err(ParseError::InvalidColor(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Role {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+impl Role {
+    pub const ALL: [Role; 6] = [
+        Role::Pawn,
+        Role::Knight,
+        Role::Bishop,
+        Role::Rook,
+        Role::Queen,
+        Role::King,
+    ];
+
+    /// The piece type used by `Chess`, without color
+    pub fn piece_type(self) -> PieceType {
+        match self {
+            Role::Pawn => PAWN,
+            Role::Knight => KNIGHT,
+            Role::Bishop => BISHOP,
+            Role::Rook => ROOK,
+            Role::Queen => QUEEN,
+            Role::King => KING,
+        }
+    }
+
+    /// The role of a piece stored by `Chess`, ignoring its color and moved mask
+    pub fn from_piece_type(piece: PieceType) -> Option<Role> {
+        match piece & 0b0011_1111 {
+            PAWN => Some(Role::Pawn),
+            KNIGHT => Some(Role::Knight),
+            BISHOP => Some(Role::Bishop),
+            ROOK => Some(Role::Rook),
+            QUEEN => Some(Role::Queen),
+            KING => Some(Role::King),
+            _ => None,
+        }
+    }
+
+    /// The lowercase letter of the role, `p` for pawns
+    pub fn char(self) -> char {
+        match self {
+            Role::Pawn => 'p',
+            Role::Knight => 'n',
+            Role::Bishop => 'b',
+            Role::Rook => 'r',
+            Role::Queen => 'q',
+            Role::King => 'k',
+        }
+    }
+
+    /// The role of a letter in either case
+    pub fn from_char(c: char) -> Option<Role> {
+        Role::ALL
+            .iter()
+            .copied()
+            .find(|role| role.char() == c.to_ascii_lowercase())
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.char())
+    }
+}
+
+impl FromStr for Role {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+
+        match (chars.next().and_then(Role::from_char), chars.next()) {
+            (Some(role), None) => Ok(role),
+            _ => Err(ParseError::InvalidRole(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Piece {
+    pub color: Color,
+    pub role: Role,
+}
+
+impl Piece {
+    pub fn new(color: Color, role: Role) -> Self {
+        Self { color, role }
+    }
+
+    /// The FEN letter of the piece, uppercase for white
+    pub fn char(self) -> char {
+        match self.color {
+            Color::White => self.role.char().to_ascii_uppercase(),
+            Color::Black => self.role.char(),
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Piece> {
+        let color = if c.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+
+        Role::from_char(c).map(|role| Piece::new(color, role))
+    }
+
+    /// The piece as stored by `Chess`, without the moved mask
+    pub fn piece_type(self) -> PieceType {
+        self.role.piece_type() | self.color.mask()
+    }
+
+    /// The piece stored by `Chess`, `None` for empty squares
+    pub fn from_piece_type(piece: PieceType) -> Option<Piece> {
+        Role::from_piece_type(piece).map(|role| Piece::new(Color::from_mask(piece), role))
+    }
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.char())
+    }
+}
+
+impl FromStr for Piece {
+    type Err = ParseError;
+
+    /// Parse a FEN letter like `N` or `q`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+
+        match (chars.next().and_then(Piece::from_char), chars.next()) {
+            (Some(piece), None) => Ok(piece),
+            _ => Err(ParseError::InvalidPiece(s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,648 @@
+use super::board::*;
+use super::piece::{Color, Piece, Role};
+use super::square::*;
+use crate::chess::{Chess, Move, MoveFlag, MoveFlags};
+use crate::errors::FenError;
+
+const WHITE_KING_SIDE: u8 = 1;
+const WHITE_QUEEN_SIDE: u8 = 2;
+const BLACK_KING_SIDE: u8 = 4;
+const BLACK_QUEEN_SIDE: u8 = 8;
+
+const CASTLING_RIGHTS: [u8; 4] = [
+    WHITE_KING_SIDE,
+    WHITE_QUEEN_SIDE,
+    BLACK_KING_SIDE,
+    BLACK_QUEEN_SIDE,
+];
+
+const PROMOTIONS: [Role; 4] = [Role::Queen, Role::Rook, Role::Bishop, Role::Knight];
+
+/// A position stored as bitboards, used to generate moves much faster than scanning the 0x88
+/// board of `Chess`. Moves are played by copying the position, so there is no undo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// the squares of each role, indexed by `Role`
+    roles: [Bitboard; 6],
+    /// the squares of each color, indexed by `Color`
+    colors: [Bitboard; 2],
+    turn: Color,
+    /// `WHITE_KING_SIDE | WHITE_QUEEN_SIDE | ...`
+    castling: u8,
+    /// the square of the rook each right castles with, in the order of `CASTLING_RIGHTS`
+    castling_rooks: [Square; 4],
+    /// castling moves are written as the king taking its own rook
+    chess960: bool,
+    en_passant: Option<Square>,
+    half_moves: u16,
+    full_moves: u16,
+}
+
+impl From<&Chess> for Position {
+    fn from(chess: &Chess) -> Self {
+        let mut position = Position {
+            roles: chess.roles,
+            colors: chess.colors,
+            turn: chess.turn(),
+            castling: 0,
+            castling_rooks: chess.castling_rooks.map(from_0x88),
+            chess960: chess.is_chess960(),
+            en_passant: chess.en_passant_square().map(from_0x88),
+            half_moves: chess.half_moves(),
+            full_moves: chess.full_moves(),
+        };
+
+        let rights = [
+            chess.can_white_king_side_castle,
+            chess.can_white_queen_side_castle,
+            chess.can_black_king_side_castle,
+            chess.can_black_queen_side_castle,
+        ];
+
+        for (&allowed, &right) in rights.iter().zip(CASTLING_RIGHTS.iter()) {
+            if allowed {
+                position.castling |= right;
+            }
+        }
+
+        position
+    }
+}
+
+impl Position {
+    pub fn from_fen(fen: &str) -> Result<Position, FenError> {
+        let mut chess = Chess::new();
+        chess.try_load_fen(fen)?;
+
+        Ok(Position::from(&chess))
+    }
+
+    pub fn turn(&self) -> Color {
+        self.turn
+    }
+
+    pub fn occupied(&self) -> Bitboard {
+        self.colors[0] | self.colors[1]
+    }
+
+    pub fn occupied_by(&self, color: Color) -> Bitboard {
+        self.colors[color as usize]
+    }
+
+    pub fn pieces(&self, color: Color, role: Role) -> Bitboard {
+        self.colors[color as usize] & self.roles[role as usize]
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        let color = if self.colors[0] & bit(square) != 0 {
+            Color::White
+        } else if self.colors[1] & bit(square) != 0 {
+            Color::Black
+        } else {
+            return None;
+        };
+
+        let role = Role::ALL
+            .iter()
+            .copied()
+            .find(|&role| self.roles[role as usize] & bit(square) != 0)?;
+
+        Some(Piece::new(color, role))
+    }
+
+    pub fn king(&self, color: Color) -> Option<Square> {
+        squares(self.pieces(color, Role::King)).next()
+    }
+
+    /// The pieces of `color` that attack the square, with the board occupied by `occupied`
+    pub fn attackers(&self, square: Square, color: Color, occupied: Bitboard) -> Bitboard {
+        let queens = self.roles[Role::Queen as usize];
+        let rooks = self.roles[Role::Rook as usize] | queens;
+        let bishops = self.roles[Role::Bishop as usize] | queens;
+
+        let attackers = (rook_attacks(square, occupied) & rooks)
+            | (bishop_attacks(square, occupied) & bishops)
+            | (knight_attacks(square) & self.roles[Role::Knight as usize])
+            | (king_attacks(square) & self.roles[Role::King as usize])
+            | (pawn_attacks(color.other(), square) & self.roles[Role::Pawn as usize]);
+
+        attackers & self.colors[color as usize]
+    }
+
+    pub fn is_attacked(&self, square: Square, by: Color) -> bool {
+        self.attackers(square, by, self.occupied()) != 0
+    }
+
+    /// Return true if the side to move is in check
+    pub fn in_check(&self) -> bool {
+        self.king(self.turn)
+            .is_some_and(|king| self.is_attacked(king, self.turn.other()))
+    }
+
+    /// Moves that follow the movement rules of the pieces, but may leave the king in check
+    pub fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(64);
+
+        self.piece_moves(&mut moves, !EMPTY, None);
+
+        if let Some(king) = self.king(self.turn) {
+            let targets = king_attacks(king) & !self.colors[self.turn as usize];
+            self.push_moves(&mut moves, king, Role::King, targets);
+        }
+
+        self.push_castling_moves(&mut moves);
+
+        moves
+    }
+
+    /// Generate the legal moves directly, without playing them.
+    ///
+    /// The checkers and pinned pieces are found first. When in check, the other pieces can only
+    /// capture the checker or block its ray, and in double check only the king can move.
+    /// Pinned pieces can only move along the line between their king and the pinner.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let us = self.turn;
+        let them = us.other();
+
+        let king = match self.king(us) {
+            Some(king) => king,
+            None => return self.pseudo_legal_moves(),
+        };
+
+        let occupied = self.occupied();
+        let checkers = self.attackers(king, them, occupied);
+        let mut moves = Vec::with_capacity(64);
+
+        if checkers.count_ones() < 2 {
+            let evasions = match squares(checkers).next() {
+                Some(checker) => checkers | between(king, checker),
+                None => !EMPTY,
+            };
+
+            self.piece_moves(&mut moves, evasions, Some((king, self.pinned(king))));
+        }
+
+        // the king can't step along the ray of a slider that is checking it, so it is taken
+        // off the board before looking for attacks
+        let without_king = occupied & !bit(king);
+        let targets = squares(king_attacks(king) & !self.colors[us as usize])
+            .filter(|&to| self.attackers(to, them, without_king) == 0)
+            .fold(EMPTY, |targets, to| targets | bit(to));
+
+        self.push_moves(&mut moves, king, Role::King, targets);
+
+        if checkers == 0 {
+            self.push_castling_moves(&mut moves);
+        }
+
+        moves
+    }
+
+    /// Return true if a pawn can legally capture en passant
+    pub fn can_capture_en_passant(&self) -> bool {
+        let us = self.turn;
+
+        let en_passant = match self.en_passant {
+            Some(en_passant) => en_passant,
+            None => return false,
+        };
+
+        let captured = match us {
+            Color::White => en_passant.offset(-8),
+            Color::Black => en_passant.offset(8),
+        };
+
+        if self.pieces(us.other(), Role::Pawn) & bit(captured) == 0 {
+            return false;
+        }
+
+        // the squares a pawn would capture from are those an enemy pawn would attack
+        let pawns = pawn_attacks(us.other(), en_passant) & self.pieces(us, Role::Pawn);
+
+        squares(pawns).any(|from| self.is_legal(from, en_passant))
+    }
+
+    /// Check that moving the piece on `from` to `to` doesn't leave its king in check, for a
+    /// move that already follows the movement rules of the piece
+    pub fn is_legal(&self, from: Square, to: Square) -> bool {
+        let us = self.turn;
+        let them = us.other();
+
+        let king = match self.king(us) {
+            Some(king) => king,
+            None => return true,
+        };
+
+        let occupied = self.occupied();
+
+        if from == king {
+            // castling is the only king move that doesn't go to a neighbouring square, or that
+            // goes to a square of its own piece in Chess960
+            if king_attacks(from) & bit(to) == 0 || self.colors[us as usize] & bit(to) != 0 {
+                return self
+                    .castling_moves()
+                    .iter()
+                    .any(|_move| _move.to == to_0x88(to));
+            }
+
+            return self.attackers(to, them, occupied & !bit(king)) == 0;
+        }
+
+        let checkers = self.attackers(king, them, occupied);
+        let is_en_passant = self.pieces(us, Role::Pawn) & bit(from) != 0
+            && from.file() != to.file()
+            && occupied & bit(to) == 0;
+
+        if let Some(checker) = squares(checkers).next() {
+            let captured = if is_en_passant {
+                Square::new_unchecked(from.rank() * 8 + to.file())
+            } else {
+                to
+            };
+
+            let evasions = checkers | between(king, checker);
+
+            if checkers.count_ones() > 1 || evasions & (bit(to) | bit(captured)) == 0 {
+                return false;
+            }
+        }
+
+        if self.pinned(king) & bit(from) != 0 && line(king, from) & bit(to) == 0 {
+            return false;
+        }
+
+        !is_en_passant || self.is_safe_en_passant(king, from, to)
+    }
+
+    /// Play a move generated for this position
+    pub fn play(&mut self, _move: Move) {
+        let us = self.turn;
+        let them = us.other();
+        let from = from_0x88(_move.from);
+        let to = from_0x88(_move.to);
+        let role = Role::from_piece_type(_move.piece).expect("a move has a piece");
+
+        if let Some(captured) = _move.captured.and_then(Role::from_piece_type) {
+            let square = match (_move.is_en_passant(), us) {
+                (false, _) => to,
+                (true, Color::White) => to.offset(-8),
+                (true, Color::Black) => to.offset(8),
+            };
+
+            self.remove(them, captured, square);
+        }
+
+        if _move.is_castle() {
+            let king_side = _move.flags & MoveFlag::KING_SIDE_CASTLE != 0;
+            let rook = self.castling_rooks[castling_right(us, king_side)];
+            let (king_to, rook_to) = castled_squares(from, king_side);
+
+            // the king and rook are both taken off first, as they can swap squares
+            self.remove(us, Role::King, from);
+            self.remove(us, Role::Rook, rook);
+            self.put(us, Role::King, king_to);
+            self.put(us, Role::Rook, rook_to);
+        } else {
+            self.remove(us, role, from);
+            self.put(
+                us,
+                _move
+                    .promotion
+                    .and_then(Role::from_piece_type)
+                    .unwrap_or(role),
+                to,
+            );
+        }
+
+        self.en_passant = if _move.flags & MoveFlag::BIG_PAWN != 0 {
+            Some(Square::new_unchecked((from.index() + to.index()) as u8 / 2))
+        } else {
+            None
+        };
+
+        // moving the king or a rook, or capturing a rook, loses the right to castle with it
+        if role == Role::King {
+            self.castling &= !(CASTLING_RIGHTS[castling_right(us, true)]
+                | CASTLING_RIGHTS[castling_right(us, false)]);
+        }
+
+        for square in [from, to] {
+            for (right, &rook) in self.castling_rooks.iter().enumerate() {
+                if rook == square {
+                    self.castling &= !CASTLING_RIGHTS[right];
+                }
+            }
+        }
+
+        if role == Role::Pawn || _move.is_capture() {
+            self.half_moves = 0;
+        } else {
+            self.half_moves = self.half_moves.saturating_add(1);
+        }
+
+        if us == Color::Black {
+            self.full_moves = self.full_moves.saturating_add(1);
+        }
+
+        self.turn = them;
+    }
+
+    /// Count the leaf nodes of the legal move tree
+    ///
+    /// https://www.chessprogramming.org/Perft
+    pub fn perft(&self, depth: u8) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.legal_moves();
+
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        moves
+            .into_iter()
+            .map(|_move| {
+                let mut position = *self;
+                position.play(_move);
+                position.perft(depth - 1)
+            })
+            .sum()
+    }
+
+    fn put(&mut self, color: Color, role: Role, square: Square) {
+        self.colors[color as usize] |= bit(square);
+        self.roles[role as usize] |= bit(square);
+    }
+
+    fn remove(&mut self, color: Color, role: Role, square: Square) {
+        self.colors[color as usize] &= !bit(square);
+        self.roles[role as usize] &= !bit(square);
+    }
+
+    fn new_move(&self, from: Square, to: Square, role: Role, flags: MoveFlags) -> Move {
+        let color = self.turn.mask();
+
+        Move {
+            from: to_0x88(from),
+            to: to_0x88(to),
+            piece: role.piece_type() | color,
+            captured: self.piece_at(to).map(Piece::piece_type),
+            promotion: None,
+            flags: if self.occupied() & bit(to) != 0 {
+                flags | MoveFlag::CAPTURE
+            } else {
+                flags
+            },
+        }
+    }
+
+    /// Add a move to each of the targets of the piece on `from`
+    fn push_moves(&self, moves: &mut Vec<Move>, from: Square, role: Role, targets: Bitboard) {
+        for to in squares(targets) {
+            moves.push(self.new_move(from, to, role, MoveFlag::NORMAL));
+        }
+    }
+
+    /// Add a pawn move, or one move per piece the pawn can promote to
+    fn push_pawn_move(&self, moves: &mut Vec<Move>, from: Square, to: Square, flags: MoveFlags) {
+        let _move = self.new_move(from, to, Role::Pawn, flags);
+
+        if bit(to) & (RANK_1 | RANK_8) == 0 {
+            moves.push(_move);
+            return;
+        }
+
+        for role in PROMOTIONS {
+            moves.push(Move {
+                promotion: Some(role.piece_type() | self.turn.mask()),
+                flags: _move.flags | MoveFlag::PROMOTION,
+                .._move
+            });
+        }
+    }
+
+    /// The moves of every piece but the king, to squares in `targets`. With the king and the
+    /// pinned pieces, the moves are legal: pinned pieces stay on the line of their pin and en
+    /// passant doesn't expose the king.
+    fn piece_moves(
+        &self,
+        moves: &mut Vec<Move>,
+        targets: Bitboard,
+        pins: Option<(Square, Bitboard)>,
+    ) {
+        let us = self.turn;
+        let occupied = self.occupied();
+        let targets = targets & !self.colors[us as usize];
+
+        self.pawn_moves(moves, targets, pins);
+
+        for from in squares(self.pieces(us, Role::Knight)) {
+            let attacks = knight_attacks(from) & pin_ray(pins, from);
+            self.push_moves(moves, from, Role::Knight, attacks & targets);
+        }
+
+        for from in squares(self.pieces(us, Role::Bishop)) {
+            let attacks = bishop_attacks(from, occupied) & pin_ray(pins, from);
+            self.push_moves(moves, from, Role::Bishop, attacks & targets);
+        }
+
+        for from in squares(self.pieces(us, Role::Rook)) {
+            let attacks = rook_attacks(from, occupied) & pin_ray(pins, from);
+            self.push_moves(moves, from, Role::Rook, attacks & targets);
+        }
+
+        for from in squares(self.pieces(us, Role::Queen)) {
+            let attacks = queen_attacks(from, occupied) & pin_ray(pins, from);
+            self.push_moves(moves, from, Role::Queen, attacks & targets);
+        }
+    }
+
+    fn pawn_moves(
+        &self,
+        moves: &mut Vec<Move>,
+        targets: Bitboard,
+        pins: Option<(Square, Bitboard)>,
+    ) {
+        let us = self.turn;
+        let theirs = self.colors[us.other() as usize];
+        let empty = !self.occupied();
+
+        let (forward, start_rank): (i8, u8) = match us {
+            Color::White => (8, 1),
+            Color::Black => (-8, 6),
+        };
+
+        for from in squares(self.pieces(us, Role::Pawn)) {
+            let ray = pin_ray(pins, from);
+            let to = from.offset(forward);
+
+            if empty & bit(to) != 0 {
+                if targets & ray & bit(to) != 0 {
+                    self.push_pawn_move(moves, from, to, MoveFlag::NORMAL);
+                }
+
+                if from.rank() == start_rank {
+                    let to = to.offset(forward);
+
+                    if empty & targets & ray & bit(to) != 0 {
+                        self.push_pawn_move(moves, from, to, MoveFlag::BIG_PAWN);
+                    }
+                }
+            }
+
+            for to in squares(pawn_attacks(us, from) & theirs & targets & ray) {
+                self.push_pawn_move(moves, from, to, MoveFlag::NORMAL);
+            }
+
+            if let Some(en_passant) = self.en_passant {
+                // the pawn that has just moved 2 squares is behind the en passant square
+                let captured = en_passant.offset(-forward);
+
+                // capturing the pawn also stops a check given by it
+                if pawn_attacks(us, from) & bit(en_passant) & ray != 0
+                    && self.pieces(us.other(), Role::Pawn) & bit(captured) != 0
+                    && targets & (bit(en_passant) | bit(captured)) != 0
+                    && pins.is_none_or(|(king, _)| self.is_safe_en_passant(king, from, en_passant))
+                {
+                    moves.push(Move {
+                        captured: Some(Role::Pawn.piece_type() | us.other().mask()),
+                        ..self.new_move(
+                            from,
+                            en_passant,
+                            Role::Pawn,
+                            MoveFlag::CAPTURE | MoveFlag::EN_PASSANT,
+                        )
+                    });
+                }
+            }
+        }
+    }
+
+    /// The pieces of the side to move that can't leave the line between their king and an
+    /// enemy slider, see `pin_ray`
+    fn pinned(&self, king: Square) -> Bitboard {
+        let us = self.turn;
+        let them = us.other();
+        let occupied = self.occupied();
+
+        let queens = self.pieces(them, Role::Queen);
+        let snipers = (rook_attacks(king, EMPTY) & (self.pieces(them, Role::Rook) | queens))
+            | (bishop_attacks(king, EMPTY) & (self.pieces(them, Role::Bishop) | queens));
+
+        squares(snipers)
+            .map(|sniper| between(king, sniper) & occupied)
+            .filter(|blockers| blockers.count_ones() == 1)
+            .fold(EMPTY, |pinned, blocker| pinned | blocker)
+            & self.colors[us as usize]
+    }
+
+    /// En passant removes two pawns from the same rank at once, which can expose the king to a
+    /// rook or queen even if neither pawn is pinned on its own
+    fn is_safe_en_passant(&self, king: Square, from: Square, to: Square) -> bool {
+        let them = self.turn.other();
+        let captured = Square::new_unchecked(from.rank() * 8 + to.file());
+        let occupied = (self.occupied() ^ bit(from) ^ bit(captured)) | bit(to);
+
+        let queens = self.pieces(them, Role::Queen);
+        let rooks = self.pieces(them, Role::Rook) | queens;
+        let bishops = self.pieces(them, Role::Bishop) | queens;
+
+        rook_attacks(king, occupied) & rooks == 0 && bishop_attacks(king, occupied) & bishops == 0
+    }
+
+    /// The legal castling moves of the side to move
+    pub fn castling_moves(&self) -> Vec<Move> {
+        let mut moves = vec![];
+        self.push_castling_moves(&mut moves);
+
+        moves
+    }
+
+    /// Castling works the same for any king and rook files: the king ends up on the g or c file
+    /// and the rook next to it on the f or d file
+    fn push_castling_moves(&self, moves: &mut Vec<Move>) {
+        let us = self.turn;
+        let them = us.other();
+
+        let king = match self.king(us) {
+            Some(king) => king,
+            None => return,
+        };
+
+        if self.is_attacked(king, them) {
+            return;
+        }
+
+        for king_side in [true, false] {
+            let right = castling_right(us, king_side);
+            let rook = self.castling_rooks[right];
+
+            if self.castling & CASTLING_RIGHTS[right] == 0
+                || self.pieces(us, Role::Rook) & bit(rook) == 0
+            {
+                continue;
+            }
+
+            let (king_to, rook_to) = castled_squares(king, king_side);
+
+            // the king and rook can be in each other's way in Chess960, so they are taken off
+            // the board before checking that the squares they go through are empty
+            let occupied = self.occupied() & !bit(king) & !bit(rook);
+            let king_path = between(king, king_to) | bit(king_to);
+            let rook_path = between(rook, rook_to) | bit(rook_to);
+
+            if occupied & (king_path | rook_path) != 0 {
+                continue;
+            }
+
+            // the king can't pass through or land on an attacked square, including one the rook
+            // was shielding
+            if squares(king_path).any(|square| self.attackers(square, them, occupied) != 0) {
+                continue;
+            }
+
+            let flags = if king_side {
+                MoveFlag::KING_SIDE_CASTLE
+            } else {
+                MoveFlag::QUEEN_SIDE_CASTLE
+            };
+
+            moves.push(Move {
+                from: to_0x88(king),
+                to: to_0x88(if self.chess960 { rook } else { king_to }),
+                piece: Role::King.piece_type() | us.mask(),
+                captured: None,
+                promotion: None,
+                flags,
+            });
+        }
+    }
+}
+
+/// The index of a castling right in `CASTLING_RIGHTS`, which is also the order `Chess` keeps
+/// its castling rooks in
+/// The squares the piece on `from` can move to without exposing its king, given the king and
+/// the pinned pieces: the line through the king for a pinned piece, every square otherwise
+fn pin_ray(pins: Option<(Square, Bitboard)>, from: Square) -> Bitboard {
+    match pins {
+        Some((king, pinned)) if pinned & bit(from) != 0 => line(king, from),
+        _ => !EMPTY,
+    }
+}
+
+pub(crate) fn castling_right(color: Color, king_side: bool) -> usize {
+    color as usize * 2 + !king_side as usize
+}
+
+/// The squares of the king and the rook after castling
+pub(crate) fn castled_squares(king: Square, king_side: bool) -> (Square, Square) {
+    let (king_file, rook_file) = if king_side { (6, 5) } else { (2, 3) };
+    let rank = king.rank();
+
+    (
+        Square::new_unchecked(rank * 8 + king_file),
+        Square::new_unchecked(rank * 8 + rook_file),
+    )
+}
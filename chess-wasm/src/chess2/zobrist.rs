@@ -0,0 +1,52 @@
+//! Random keys for Zobrist hashing. The key of a position is the XOR of the keys of its pieces,
+//! castling rights, en passant file and side to move, so it can be updated one piece at a time.
+//!
+//! https://www.chessprogramming.org/Zobrist_Hashing
+
+use super::piece::{Color, Role};
+use super::square::Square;
+
+const PIECE_KEYS: usize = 2 * 6 * 64;
+const CASTLING_KEYS: usize = PIECE_KEYS;
+const EN_PASSANT_KEYS: usize = CASTLING_KEYS + 4;
+const TURN_KEY: usize = EN_PASSANT_KEYS + 8;
+
+/// SplitMix64, which is good enough to fill the table at compile time
+const fn generate() -> [u64; TURN_KEY + 1] {
+    let mut keys = [0; TURN_KEY + 1];
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut i = 0;
+
+    while i < keys.len() {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[i] = z ^ (z >> 31);
+
+        i += 1;
+    }
+
+    keys
+}
+
+static KEYS: [u64; TURN_KEY + 1] = generate();
+
+pub fn piece(color: Color, role: Role, square: Square) -> u64 {
+    KEYS[(color as usize * 6 + role as usize) * 64 + square.index()]
+}
+
+/// `right` is 0 for white king side, 1 for white queen side, 2 and 3 for black
+pub fn castling(right: usize) -> u64 {
+    KEYS[CASTLING_KEYS + right]
+}
+
+/// Only hashed when an en passant capture is actually possible
+pub fn en_passant(file: u8) -> u64 {
+    KEYS[EN_PASSANT_KEYS + file as usize]
+}
+
+pub fn black_to_move() -> u64 {
+    KEYS[TURN_KEY]
+}
@@ -0,0 +1,76 @@
+//! The 960 starting positions of Fischer Random chess, numbered as in Scharnagl's scheme where
+//! 518 is the standard position.
+//!
+//! https://www.chessprogramming.org/Reinhard_Scharnagl#Chess960Numbering
+
+use crate::chess2::Role;
+
+/// The placements of the two knights on the five squares left after the bishops and queen
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// The pieces on the back rank from the a-file to the h-file, `None` if `index` isn't below 960
+pub fn back_rank(index: u16) -> Option<[Role; 8]> {
+    if index >= 960 {
+        return None;
+    }
+
+    let mut rank: [Option<Role>; 8] = [None; 8];
+    let mut n = index as usize;
+
+    // a bishop on each color, light squares first
+    rank[n % 4 * 2 + 1] = Some(Role::Bishop);
+    n /= 4;
+    rank[n % 4 * 2] = Some(Role::Bishop);
+    n /= 4;
+
+    place(&mut rank, n % 6, Role::Queen);
+    n /= 6;
+
+    // the second knight is placed after the first, so it has one empty square less to skip
+    let (first, second) = KNIGHTS[n];
+    place(&mut rank, first, Role::Knight);
+    place(&mut rank, second - 1, Role::Knight);
+
+    // the king is always between the rooks
+    for role in [Role::Rook, Role::King, Role::Rook] {
+        place(&mut rank, 0, role);
+    }
+
+    Some(rank.map(|role| role.expect("every square has a piece")))
+}
+
+/// The FEN of a starting position, `None` if `index` isn't below 960
+pub fn starting_fen(index: u16) -> Option<String> {
+    let white: String = back_rank(index)?
+        .iter()
+        .map(|role| role.char().to_ascii_uppercase())
+        .collect();
+
+    Some(format!(
+        "{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1",
+        white.to_ascii_lowercase(),
+        white
+    ))
+}
+
+/// Put the piece on the empty square that comes after `skip` other empty squares
+fn place(rank: &mut [Option<Role>; 8], skip: usize, role: Role) {
+    let square = rank
+        .iter_mut()
+        .filter(|square| square.is_none())
+        .nth(skip)
+        .expect("there are enough empty squares");
+
+    *square = Some(role);
+}
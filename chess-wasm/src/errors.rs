@@ -22,4 +22,92 @@ pub enum MoveError {
 
     #[error("Invalid promotion")]
     InvalidPromotion,
+
+    #[error("Illegal move")]
+    IllegalMove,
+
+    #[error("Invalid move notation '{0}'")]
+    InvalidNotation(String),
+
+    #[error("Invalid square '{0}'")]
+    InvalidSquare(String),
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum FenError {
+    #[error("Expected 4 to 6 fields, found {0}")]
+    WrongFieldCount(usize),
+
+    #[error("Expected 8 ranks, found {0}")]
+    WrongRankCount(usize),
+
+    #[error("Invalid piece '{0}' on rank {1}")]
+    InvalidPiece(char, u8),
+
+    #[error("Too many squares on rank {0}")]
+    RankOverflow(u8),
+
+    #[error("Too few squares on rank {0}")]
+    RankUnderflow(u8),
+
+    #[error("Missing {0} king")]
+    MissingKing(&'static str),
+
+    #[error("Too many {0} kings")]
+    TooManyKings(&'static str),
+
+    #[error("Pawn on back rank at {0}")]
+    PawnOnBackRank(String),
+
+    #[error("Invalid turn '{0}'")]
+    InvalidTurn(String),
+
+    #[error("Invalid castling rights '{0}'")]
+    InvalidCastlingRights(String),
+
+    #[error("Invalid en passant square '{0}'")]
+    InvalidEnPassantSquare(String),
+
+    #[error("En passant square {0} is on the wrong rank")]
+    EnPassantWrongRank(String),
+
+    #[error("Invalid halfmove clock '{0}'")]
+    InvalidHalfMoves(String),
+
+    #[error("Invalid fullmove number '{0}'")]
+    InvalidFullMoves(String),
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PgnError {
+    #[error("Syntax error on line {line}: {message}")]
+    Syntax { line: usize, message: String },
+
+    #[error("Invalid FEN tag: {0}")]
+    InvalidFen(#[from] FenError),
+
+    #[error("Can't play {san} at ply {ply}: {error}")]
+    IllegalMove {
+        ply: usize,
+        san: String,
+        error: MoveError,
+    },
+
+    #[error("Can't read the PGN: {0}")]
+    Io(String),
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("Invalid square '{0}'")]
+    InvalidSquare(String),
+
+    #[error("Invalid color '{0}'")]
+    InvalidColor(String),
+
+    #[error("Invalid role '{0}'")]
+    InvalidRole(String),
+
+    #[error("Invalid piece '{0}'")]
+    InvalidPiece(String),
 }
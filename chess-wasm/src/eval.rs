@@ -0,0 +1,522 @@
+//! Static evaluation of a position.
+//!
+//! Every term is scored twice, for the middlegame and for the endgame, and the two are blended
+//! by the material left on the board: with all the pieces the middlegame score counts alone,
+//! with only kings and pawns the endgame one does.
+//!
+//! The weights are all in `WEIGHTS`. `breakdown_with` evaluates with other weights, to tune
+//! them.
+
+use crate::chess::Chess;
+use crate::chess2::board::*;
+use crate::chess2::{Color, Position, Role, Square};
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Sub};
+
+/// The phase of a position with all the pieces, each knight and bishop counts 1, each rook 2
+/// and each queen 4
+pub const MAX_PHASE: i32 = 24;
+
+const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+
+/// A middlegame and an endgame score, in centipawns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Score {
+    pub const ZERO: Score = Score { mg: 0, eg: 0 };
+
+    pub const fn new(mg: i32, eg: i32) -> Score {
+        Score { mg, eg }
+    }
+
+    /// Blend the two scores by the phase, from 0 for a pawn endgame to `MAX_PHASE`
+    pub fn taper(self, phase: i32) -> i32 {
+        let phase = phase.clamp(0, MAX_PHASE);
+
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        Score::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, other: Score) -> Score {
+        Score::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Score;
+
+    fn mul(self, n: i32) -> Score {
+        Score::new(self.mg * n, self.eg * n)
+    }
+}
+
+const fn s(mg: i32, eg: i32) -> Score {
+    Score::new(mg, eg)
+}
+
+/// Everything the evaluation can be tuned with. Arrays by role are in the order of
+/// `Role::ALL`, piece-square tables are from white's point of view with a8 first, as the
+/// board is printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weights {
+    pub material: [Score; 6],
+    pub piece_squares_mg: [[i32; 64]; 6],
+    pub piece_squares_eg: [[i32; 64]; 6],
+    /// for each pawn on a file after the first
+    pub doubled_pawn: Score,
+    /// for each pawn without friendly pawns on the files next to it
+    pub isolated_pawn: Score,
+    /// by rank, counted from the pawn's side of the board
+    pub passed_pawn: [Score; 8],
+    /// for each square a piece attacks that isn't taken by its own pieces or attacked by
+    /// enemy pawns, by role
+    pub mobility: [Score; 6],
+    /// for each friendly pawn on the two ranks in front of the king
+    pub pawn_shield: Score,
+    /// for each file around the king without friendly pawns
+    pub open_file_near_king: Score,
+    /// for each attack of an enemy piece on the king or the squares around it
+    pub king_zone_attack: Score,
+    pub bishop_pair: Score,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        WEIGHTS
+    }
+}
+
+#[rustfmt::skip]
+pub const WEIGHTS: Weights = Weights {
+    material: [s(100, 120), s(320, 300), s(330, 320), s(500, 550), s(950, 1000), s(0, 0)],
+    piece_squares_mg: [PAWN_MG, KNIGHT, BISHOP, ROOK, QUEEN, KING_MG],
+    piece_squares_eg: [PAWN_EG, KNIGHT, BISHOP, ROOK, QUEEN, KING_EG],
+    doubled_pawn: s(-10, -20),
+    isolated_pawn: s(-10, -15),
+    passed_pawn: [s(0, 0), s(5, 10), s(10, 15), s(15, 30), s(30, 55), s(50, 90), s(80, 140), s(0, 0)],
+    mobility: [s(0, 0), s(4, 4), s(5, 5), s(2, 4), s(1, 2), s(0, 0)],
+    pawn_shield: s(12, 0),
+    open_file_near_king: s(-20, 0),
+    king_zone_attack: s(-8, 0),
+    bishop_pair: s(30, 50),
+};
+
+#[rustfmt::skip]
+const PAWN_MG: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    10,  10,  20,  30,  30,  20,  10,  10,
+     5,   5,  10,  25,  25,  10,   5,   5,
+     0,   0,   0,  20,  20,   0,   0,   0,
+     5,  -5, -10,   0,   0, -10,  -5,   5,
+     5,  10,  10, -20, -20,  10,  10,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const PAWN_EG: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    80,  80,  80,  80,  80,  80,  80,  80,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    30,  30,  30,  30,  30,  30,  30,  30,
+    15,  15,  15,  15,  15,  15,  15,  15,
+     5,   5,   5,   5,   5,   5,   5,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+   -50, -40, -30, -30, -30, -30, -40, -50,
+   -40, -20,   0,   0,   0,   0, -20, -40,
+   -30,   0,  10,  15,  15,  10,   0, -30,
+   -30,   5,  15,  20,  20,  15,   5, -30,
+   -30,   0,  15,  20,  20,  15,   0, -30,
+   -30,   5,  10,  15,  15,  10,   5, -30,
+   -40, -20,   0,   5,   5,   0, -20, -40,
+   -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+   -20, -10, -10, -10, -10, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,  10,  10,   5,   0, -10,
+   -10,   5,   5,  10,  10,   5,   5, -10,
+   -10,   0,  10,  10,  10,  10,   0, -10,
+   -10,  10,  10,  10,  10,  10,  10, -10,
+   -10,   5,   0,   0,   0,   0,   5, -10,
+   -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+     5,  10,  10,  10,  10,  10,  10,   5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+     0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,   5,   5,   5,   0, -10,
+    -5,   0,   5,   5,   5,   5,   0,  -5,
+     0,   0,   5,   5,   5,   5,   0,  -5,
+   -10,   5,   5,   5,   5,   5,   0, -10,
+   -10,   0,   5,   0,   0,   0,   0, -10,
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const KING_MG: [i32; 64] = [
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -20, -30, -30, -40, -40, -30, -30, -20,
+   -10, -20, -20, -20, -20, -20, -20, -10,
+    20,  20,   0,   0,   0,   0,  20,  20,
+    20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+#[rustfmt::skip]
+const KING_EG: [i32; 64] = [
+   -50, -40, -30, -20, -20, -30, -40, -50,
+   -30, -20, -10,   0,   0, -10, -20, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -30,   0,   0,   0,   0, -30, -30,
+   -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+/// The parts the evaluation is made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Term {
+    Material,
+    PieceSquares,
+    PawnStructure,
+    Mobility,
+    KingSafety,
+    BishopPair,
+}
+
+impl Term {
+    pub const ALL: [Term; 6] = [
+        Term::Material,
+        Term::PieceSquares,
+        Term::PawnStructure,
+        Term::Mobility,
+        Term::KingSafety,
+        Term::BishopPair,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Term::Material => "material",
+            Term::PieceSquares => "piece-squares",
+            Term::PawnStructure => "pawn-structure",
+            Term::Mobility => "mobility",
+            Term::KingSafety => "king-safety",
+            Term::BishopPair => "bishop-pair",
+        }
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// What a term is worth to each player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TermScore {
+    pub term: Term,
+    pub white: Score,
+    pub black: Score,
+}
+
+/// The evaluation of a position term by term, to explain it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakdown {
+    pub turn: Color,
+    /// from 0 for a pawn endgame to `MAX_PHASE` with all the pieces on the board
+    pub phase: i32,
+    /// in the order of `Term::ALL`
+    pub terms: [TermScore; 6],
+}
+
+impl Breakdown {
+    pub fn term(&self, term: Term) -> TermScore {
+        self.terms[term as usize]
+    }
+
+    /// What a term is worth to `color`, tapered by the phase
+    pub fn value(&self, term: Term, color: Color) -> i32 {
+        let TermScore { white, black, .. } = self.term(term);
+
+        match color {
+            Color::White => (white - black).taper(self.phase),
+            Color::Black => (black - white).taper(self.phase),
+        }
+    }
+
+    /// The evaluation from the point of view of the player to move, the sum of the terms
+    pub fn total(&self) -> i32 {
+        Term::ALL
+            .iter()
+            .map(|&term| self.value(term, self.turn))
+            .sum()
+    }
+}
+
+/// The static evaluation of the position, in centipawns from the point of view of the player
+/// to move
+pub fn evaluate(chess: &Chess) -> i32 {
+    breakdown(chess).total()
+}
+
+pub fn breakdown(chess: &Chess) -> Breakdown {
+    breakdown_with(chess, &WEIGHTS)
+}
+
+pub fn breakdown_with(chess: &Chess, weights: &Weights) -> Breakdown {
+    let position = Position::from(chess);
+
+    let terms = Term::ALL.map(|term| TermScore {
+        term,
+        white: score(&position, weights, term, Color::White),
+        black: score(&position, weights, term, Color::Black),
+    });
+
+    Breakdown {
+        turn: position.turn(),
+        phase: phase(&position),
+        terms,
+    }
+}
+
+fn phase(position: &Position) -> i32 {
+    let count = |role| {
+        (position.pieces(Color::White, role) | position.pieces(Color::Black, role)).count_ones()
+            as i32
+    };
+
+    let phase =
+        count(Role::Knight) + count(Role::Bishop) + 2 * count(Role::Rook) + 4 * count(Role::Queen);
+
+    phase.min(MAX_PHASE)
+}
+
+fn score(position: &Position, weights: &Weights, term: Term, color: Color) -> Score {
+    match term {
+        Term::Material => material(position, weights, color),
+        Term::PieceSquares => piece_squares(position, weights, color),
+        Term::PawnStructure => pawn_structure(position, weights, color),
+        Term::Mobility => mobility(position, weights, color),
+        Term::KingSafety => king_safety(position, weights, color),
+        Term::BishopPair => {
+            if position.pieces(color, Role::Bishop).count_ones() >= 2 {
+                weights.bishop_pair
+            } else {
+                Score::ZERO
+            }
+        }
+    }
+}
+
+fn material(position: &Position, weights: &Weights, color: Color) -> Score {
+    let mut score = Score::ZERO;
+
+    for &role in Role::ALL.iter() {
+        score += weights.material[role as usize] * position.pieces(color, role).count_ones() as i32;
+    }
+
+    score
+}
+
+fn piece_squares(position: &Position, weights: &Weights, color: Color) -> Score {
+    let mut score = Score::ZERO;
+
+    for &role in Role::ALL.iter() {
+        for square in squares(position.pieces(color, role)) {
+            // the tables start with a8, and black's are mirrored
+            let index = match color {
+                Color::White => square.index() ^ 56,
+                Color::Black => square.index(),
+            };
+
+            score += s(
+                weights.piece_squares_mg[role as usize][index],
+                weights.piece_squares_eg[role as usize][index],
+            );
+        }
+    }
+
+    score
+}
+
+fn pawn_structure(position: &Position, weights: &Weights, color: Color) -> Score {
+    let pawns = position.pieces(color, Role::Pawn);
+    let enemy_pawns = position.pieces(color.other(), Role::Pawn);
+    let mut score = Score::ZERO;
+
+    for file in 0..8 {
+        let count = (pawns & (FILE_A << file)).count_ones() as i32;
+
+        if count > 1 {
+            score += weights.doubled_pawn * (count - 1);
+        }
+    }
+
+    for square in squares(pawns) {
+        if pawns & adjacent_files(square.file()) == 0 {
+            score += weights.isolated_pawn;
+        }
+
+        let span = (adjacent_files(square.file()) | (FILE_A << square.file()))
+            & ranks_ahead(color, square.rank());
+
+        if enemy_pawns & span == 0 {
+            score += weights.passed_pawn[relative_rank(color, square) as usize];
+        }
+    }
+
+    score
+}
+
+fn mobility(position: &Position, weights: &Weights, color: Color) -> Score {
+    let occupied = position.occupied();
+    let available = !position.occupied_by(color) & !pawn_attacks_of(position, color.other());
+    let mut score = Score::ZERO;
+
+    for &role in [Role::Knight, Role::Bishop, Role::Rook, Role::Queen].iter() {
+        for square in squares(position.pieces(color, role)) {
+            let moves = (attacks(role, square, occupied) & available).count_ones() as i32;
+
+            score += weights.mobility[role as usize] * moves;
+        }
+    }
+
+    score
+}
+
+fn king_safety(position: &Position, weights: &Weights, color: Color) -> Score {
+    let king = match position.king(color) {
+        Some(king) => king,
+        None => return Score::ZERO,
+    };
+
+    let pawns = position.pieces(color, Role::Pawn);
+    let files = adjacent_files(king.file()) | (FILE_A << king.file());
+    let two_ranks_ahead =
+        ranks_ahead(color, king.rank()) & !ranks_ahead(color, forward(color, king.rank(), 2));
+    let mut score = Score::ZERO;
+
+    score += weights.pawn_shield * (pawns & files & two_ranks_ahead).count_ones() as i32;
+
+    for file in king.file().saturating_sub(1)..=(king.file() + 1).min(7) {
+        if pawns & (FILE_A << file) == 0 {
+            score += weights.open_file_near_king;
+        }
+    }
+
+    let zone = king_attacks(king) | bit(king);
+    let occupied = position.occupied();
+    let enemy = color.other();
+
+    for &role in [Role::Knight, Role::Bishop, Role::Rook, Role::Queen].iter() {
+        for square in squares(position.pieces(enemy, role)) {
+            let attacks = (attacks(role, square, occupied) & zone).count_ones() as i32;
+
+            score += weights.king_zone_attack * attacks;
+        }
+    }
+
+    score
+}
+
+fn attacks(role: Role, square: Square, occupied: Bitboard) -> Bitboard {
+    match role {
+        Role::Knight => knight_attacks(square),
+        Role::Bishop => bishop_attacks(square, occupied),
+        Role::Rook => rook_attacks(square, occupied),
+        Role::Queen => queen_attacks(square, occupied),
+        Role::King => king_attacks(square),
+        Role::Pawn => EMPTY,
+    }
+}
+
+fn pawn_attacks_of(position: &Position, color: Color) -> Bitboard {
+    squares(position.pieces(color, Role::Pawn))
+        .map(|square| pawn_attacks(color, square))
+        .fold(EMPTY, |attacks, square_attacks| attacks | square_attacks)
+}
+
+fn adjacent_files(file: u8) -> Bitboard {
+    let left = if file > 0 {
+        FILE_A << (file - 1)
+    } else {
+        EMPTY
+    };
+    let right = if file < 7 {
+        FILE_A << (file + 1)
+    } else {
+        EMPTY
+    };
+
+    left | right
+}
+
+/// The ranks in front of `rank` from `color`'s side of the board
+fn ranks_ahead(color: Color, rank: u8) -> Bitboard {
+    match color {
+        Color::White if rank < 7 => !0 << (8 * (rank + 1)),
+        Color::Black => (1 << (8 * rank)) - 1,
+        _ => EMPTY,
+    }
+}
+
+/// The rank `n` ranks in front of `rank`, or the last one
+fn forward(color: Color, rank: u8, n: u8) -> u8 {
+    match color {
+        Color::White => (rank + n).min(7),
+        Color::Black => rank.saturating_sub(n),
+    }
+}
+
+fn relative_rank(color: Color, square: Square) -> u8 {
+    match color {
+        Color::White => square.rank(),
+        Color::Black => 7 - square.rank(),
+    }
+}
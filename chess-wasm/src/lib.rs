@@ -1,8 +1,18 @@
 pub mod chess;
 pub mod chess2;
+pub mod chess960;
 pub mod errors;
+pub mod eval;
+pub mod move_picker;
+pub mod outcome;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod perft;
+pub mod pgn;
+pub mod san;
+pub mod search;
+pub mod transposition;
 mod utils;
-use std::rc::{self, Rc};
+pub mod wasm;
 
 use wasm_bindgen::prelude::*;
 
@@ -19,5 +29,5 @@ extern "C" {
 
 #[wasm_bindgen]
 pub fn greet() {
-    let board: [u8; 128] = [0; 128];
+    let _board: [u8; 128] = [0; 128];
 }
@@ -0,0 +1,400 @@
+//! Staged move ordering.
+//!
+//! Alpha-beta cuts off as soon as a move is good enough, so the sooner the best move is tried
+//! the less is searched. `MovePicker` returns the legal moves of a position in stages, from
+//! the most to the least likely to be good:
+//!
+//! 1. the hash move, the best move found for the position before
+//! 2. the captures and promotions that don't lose material, the most valuable victims by the
+//!    least valuable attackers first
+//! 3. the killer moves, quiet moves that caused a cutoff at the same ply in a sibling position
+//! 4. the counter-move, the quiet move that last refuted the opponent's previous move
+//! 5. the other quiet moves, by how often they caused cutoffs anywhere in the tree
+//! 6. the captures that lose material, by the static exchange evaluation
+//!
+//! Each stage is picked best first as it is reached, so the moves of the later stages aren't
+//! sorted if an early move cuts off. What the search learns about quiet moves is kept in a
+//! `History`.
+
+use crate::chess::{Chess, Move};
+use crate::chess2::board::{bit, squares, Bitboard};
+use crate::chess2::{Color, Position, Role, Square};
+use crate::eval::WEIGHTS;
+use crate::search::MAX_PLY;
+
+/// History scores stay within this, so recent cutoffs outweigh old ones
+const MAX_HISTORY: i32 = 16_384;
+
+/// The stage a move was picked in, in the order they are picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    HashMove,
+    GoodCaptures,
+    Killers,
+    CounterMove,
+    Quiets,
+    BadCaptures,
+}
+
+/// The quiet moves that caused cutoffs, kept from one position of the search to the next
+#[derive(Debug, Clone)]
+pub struct History {
+    /// two per ply, the latest first
+    killers: Vec<[Option<Move>; 2]>,
+    /// by the piece and destination of the move refuted
+    counter_moves: Vec<Option<Move>>,
+    /// by the color, origin and destination of the move
+    scores: Vec<i32>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History {
+            killers: vec![[None; 2]; MAX_PLY + 1],
+            counter_moves: vec![None; 12 * 64],
+            scores: vec![0; 2 * 64 * 64],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = History::new();
+    }
+
+    /// Start a new search. The killers were found at the plies of the previous search, so
+    /// they are forgotten, while the scores are halved.
+    pub fn new_search(&mut self) {
+        self.killers.fill([None; 2]);
+
+        for score in self.scores.iter_mut() {
+            *score /= 2;
+        }
+    }
+
+    pub fn killers(&self, ply: usize) -> [Option<Move>; 2] {
+        self.killers.get(ply).copied().unwrap_or([None; 2])
+    }
+
+    /// The move that last refuted `previous`
+    pub fn counter_move(&self, previous: Move) -> Option<Move> {
+        self.counter_moves[counter_index(previous)]
+    }
+
+    pub fn score(&self, _move: Move) -> i32 {
+        self.scores[score_index(_move)]
+    }
+
+    /// Record that the quiet move `_move` caused a cutoff at `ply`, `depth` plies from the
+    /// horizon, in reply to `previous`. The quiet moves tried before it, which didn't, are
+    /// scored down.
+    pub fn record_cutoff(
+        &mut self,
+        ply: usize,
+        depth: i32,
+        previous: Option<Move>,
+        _move: Move,
+        tried: &[Move],
+    ) {
+        if let Some(killers) = self.killers.get_mut(ply) {
+            if killers[0] != Some(_move) {
+                killers[1] = killers[0];
+                killers[0] = Some(_move);
+            }
+        }
+
+        if let Some(previous) = previous {
+            self.counter_moves[counter_index(previous)] = Some(_move);
+        }
+
+        let bonus = (depth * depth).min(MAX_HISTORY);
+
+        self.update_score(_move, bonus);
+
+        for &other in tried {
+            self.update_score(other, -bonus);
+        }
+    }
+
+    fn update_score(&mut self, _move: Move, bonus: i32) {
+        let score = &mut self.scores[score_index(_move)];
+
+        // the closer to the bound, the less it moves
+        *score += bonus - *score * bonus.abs() / MAX_HISTORY;
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new()
+    }
+}
+
+fn counter_index(_move: Move) -> usize {
+    let piece = _move.moved_piece();
+
+    (piece.color as usize * 6 + piece.role as usize) * 64 + _move.to_square().index()
+}
+
+fn score_index(_move: Move) -> usize {
+    let color = _move.moved_piece().color as usize;
+
+    (color * 64 + _move.from_square().index()) * 64 + _move.to_square().index()
+}
+
+/// The legal moves of a position, in stages. See the module documentation.
+#[derive(Debug, Clone)]
+pub struct MovePicker {
+    /// the stage moves are picked from
+    current: Stage,
+    /// the stage of the last move picked
+    stage: Stage,
+    legal_moves: usize,
+    hash_move: Option<Move>,
+    good_captures: Vec<(Move, i32)>,
+    killers: Vec<(Move, i32)>,
+    counter_move: Option<Move>,
+    quiets: Vec<(Move, i32)>,
+    bad_captures: Vec<(Move, i32)>,
+}
+
+impl MovePicker {
+    /// All the legal moves of the position of `chess`. The hash move is only picked if it is
+    /// legal, the killers and counter-move of `history` only if they are legal quiet moves.
+    pub fn new(
+        chess: &Chess,
+        hash_move: Option<Move>,
+        history: &History,
+        ply: usize,
+        previous: Option<Move>,
+    ) -> MovePicker {
+        let killers = history.killers(ply);
+        let counter_move = previous.and_then(|previous| history.counter_move(previous));
+
+        let mut picker = MovePicker::with_moves(chess, hash_move, true);
+        let mut quiets = vec![];
+
+        for (_move, _) in picker.quiets.drain(..) {
+            if let Some(index) = killers.iter().position(|&killer| killer == Some(_move)) {
+                picker.killers.push((_move, -(index as i32)));
+            } else if Some(_move) == counter_move {
+                picker.counter_move = Some(_move);
+            } else {
+                quiets.push((_move, history.score(_move)));
+            }
+        }
+
+        picker.quiets = quiets;
+        picker
+    }
+
+    /// Only the captures and promotions of the position of `chess`, for a quiescence search.
+    /// The quiet moves are left out, so there are no killers or counter-moves.
+    pub fn tactical(chess: &Chess, hash_move: Option<Move>) -> MovePicker {
+        MovePicker::with_moves(chess, hash_move, false)
+    }
+
+    fn with_moves(chess: &Chess, hash_move: Option<Move>, quiets: bool) -> MovePicker {
+        let position = Position::from(chess);
+        let moves = position.legal_moves();
+
+        let mut picker = MovePicker {
+            current: Stage::HashMove,
+            stage: Stage::HashMove,
+            legal_moves: moves.len(),
+            hash_move: None,
+            good_captures: vec![],
+            killers: vec![],
+            counter_move: None,
+            quiets: vec![],
+            bad_captures: vec![],
+        };
+
+        for _move in moves {
+            let tactical = is_tactical(_move);
+
+            if !tactical && !quiets {
+                continue;
+            }
+
+            if Some(_move) == hash_move {
+                picker.hash_move = Some(_move);
+            } else if !tactical {
+                picker.quiets.push((_move, 0));
+            } else {
+                let see = see(&position, _move);
+
+                if see >= 0 {
+                    picker.good_captures.push((_move, mvv_lva(_move)));
+                } else {
+                    picker.bad_captures.push((_move, see));
+                }
+            }
+        }
+
+        picker
+    }
+
+    /// The stage the last move was picked in
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    /// Whether the position has legal moves, even ones that aren't picked
+    pub fn has_legal_moves(&self) -> bool {
+        self.legal_moves > 0
+    }
+}
+
+impl Iterator for MovePicker {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        loop {
+            let picked = match self.current {
+                Stage::HashMove => self.hash_move.take(),
+                Stage::GoodCaptures => pick_best(&mut self.good_captures),
+                Stage::Killers => pick_best(&mut self.killers),
+                Stage::CounterMove => self.counter_move.take(),
+                Stage::Quiets => pick_best(&mut self.quiets),
+                Stage::BadCaptures => pick_best(&mut self.bad_captures),
+            };
+
+            if picked.is_some() {
+                self.stage = self.current;
+                return picked;
+            }
+
+            self.current = match self.current {
+                Stage::HashMove => Stage::GoodCaptures,
+                Stage::GoodCaptures => Stage::Killers,
+                Stage::Killers => Stage::CounterMove,
+                Stage::CounterMove => Stage::Quiets,
+                Stage::Quiets => Stage::BadCaptures,
+                Stage::BadCaptures => return None,
+            };
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.hash_move.iter().count()
+            + self.good_captures.len()
+            + self.killers.len()
+            + self.counter_move.iter().count()
+            + self.quiets.len()
+            + self.bad_captures.len();
+
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for MovePicker {}
+
+/// Remove the move with the highest score, the first one on ties
+fn pick_best(moves: &mut Vec<(Move, i32)>) -> Option<Move> {
+    let mut best: Option<usize> = None;
+
+    for (index, &(_, score)) in moves.iter().enumerate() {
+        if best.is_none_or(|best| score > moves[best].1) {
+            best = Some(index);
+        }
+    }
+
+    best.map(|index| moves.remove(index).0)
+}
+
+/// Captures and promotions, the moves that change the material
+pub fn is_tactical(_move: Move) -> bool {
+    _move.is_capture() || _move.is_promotion()
+}
+
+/// The value of the pieces when ordering captures, their middlegame material weight. The
+/// king is worth nothing, it is never captured.
+pub fn value(role: Role) -> i32 {
+    WEIGHTS.material[role as usize].mg
+}
+
+/// Most valuable victim, least valuable attacker. Promotions count as capturing the new piece, and
+/// the king as the least valuable attacker since it can only capture undefended pieces.
+pub fn mvv_lva(_move: Move) -> i32 {
+    let victim = _move.captured_piece().map_or(0, |piece| value(piece.role))
+        + _move.promotion_role().map_or(0, value);
+
+    if victim == 0 {
+        return 0;
+    }
+
+    victim * 10 - value(_move.moved_piece().role) / 10
+}
+
+/// Static exchange evaluation: the material won by `_move` once both players have captured
+/// on its destination square with their least valuable pieces, each stopping when capturing
+/// again would lose material. Quiet moves score what is lost if the moved piece is taken.
+pub fn see(position: &Position, _move: Move) -> i32 {
+    if _move.is_castle() {
+        return 0;
+    }
+
+    let from = _move.from_square();
+    let to = _move.to_square();
+
+    let mut occupied = position.occupied() & !bit(from);
+
+    if _move.is_en_passant() {
+        let captured = Square::from_coords(to.file(), from.rank()).expect("on the board");
+        occupied &= !bit(captured);
+    }
+
+    let promotion = _move.promotion_role();
+
+    // gains[n] is what the nth capture wins, if the exchange stopped after it
+    let mut gains = vec![
+        _move.captured_piece().map_or(0, |piece| value(piece.role))
+            + promotion.map_or(0, |role| value(role) - value(Role::Pawn)),
+    ];
+
+    let mut on_square = value(promotion.unwrap_or(_move.moved_piece().role));
+    let mut color = _move.moved_piece().color;
+
+    loop {
+        color = color.other();
+
+        let attackers = position.attackers(to, color, occupied) & occupied;
+
+        let (role, square) = match least_valuable(position, attackers, color) {
+            Some(attacker) => attacker,
+            None => break,
+        };
+
+        // the king can't capture a defended piece
+        if role == Role::King
+            && position.attackers(to, color.other(), occupied & !bit(square)) & occupied != 0
+        {
+            break;
+        }
+
+        gains.push(on_square - gains[gains.len() - 1]);
+        on_square = value(role);
+        occupied &= !bit(square);
+    }
+
+    // each player only captures if it gains more than stopping
+    while gains.len() > 1 {
+        let last = gains.pop().expect("more than one gain");
+        let previous = gains.last_mut().expect("more than one gain");
+
+        *previous = -(-*previous).max(last);
+    }
+
+    gains[0]
+}
+
+fn least_valuable(
+    position: &Position,
+    attackers: Bitboard,
+    color: Color,
+) -> Option<(Role, Square)> {
+    Role::ALL.iter().find_map(|&role| {
+        squares(attackers & position.pieces(color, role))
+            .next()
+            .map(|square| (role, square))
+    })
+}
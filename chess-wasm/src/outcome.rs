@@ -0,0 +1,49 @@
+//! How a game ended, see `Chess::outcome`
+
+use crate::chess2::Color;
+
+/// Why the game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    InsufficientMaterial,
+    /// claimed after 50 moves by each player without a pawn move or capture
+    FiftyMoves,
+    /// automatic after 75 moves by each player without a pawn move or capture
+    SeventyFiveMoves,
+    /// claimed when the same position occurs for the third time
+    ThreefoldRepetition,
+    /// automatic when the same position occurs for the fifth time
+    FivefoldRepetition,
+    Resignation,
+    Timeout,
+    Agreement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Outcome {
+    /// `None` for a draw
+    pub winner: Option<Color>,
+    pub termination: Termination,
+}
+
+impl Outcome {
+    pub fn win(winner: Color, termination: Termination) -> Self {
+        Self {
+            winner: Some(winner),
+            termination,
+        }
+    }
+
+    pub fn draw(termination: Termination) -> Self {
+        Self {
+            winner: None,
+            termination,
+        }
+    }
+
+    pub fn is_draw(&self) -> bool {
+        self.winner.is_none()
+    }
+}
@@ -0,0 +1,306 @@
+//! Perft divide, compared move by move against a UCI engine to track down move generation bugs.
+//!
+//! The engine is asked for `go perft` from the same position. When the counts differ, the first
+//! move with a different count is played and both sides are asked again one ply deeper, until the
+//! two lists of moves differ. That position and the moves leading to it are the smallest case
+//! that reproduces the bug.
+
+use crate::chess::Chess;
+use crate::transposition::{self, TranspositionTable};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+pub trait Engine {
+    /// Count the leaf nodes below each legal move of the position reached by playing `moves`
+    /// (in UCI notation) from `fen`
+    fn perft(&mut self, fen: &str, moves: &[String], depth: u8) -> io::Result<Perft>;
+}
+
+/// The node count of each legal move, keyed by its UCI notation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Perft {
+    total_count: u64,
+    child_count: BTreeMap<String, u64>,
+}
+
+impl Perft {
+    pub fn new(total_count: u64, child_count: BTreeMap<String, u64>) -> Perft {
+        Perft {
+            total_count,
+            child_count,
+        }
+    }
+
+    /// Divide the position of `chess` with its own move generator
+    pub fn divide(chess: &mut Chess, depth: u8) -> Perft {
+        Perft::divide_by(chess, depth, |chess, depth| chess.perft(depth))
+    }
+
+    /// Divide the position of `chess`, caching the counts of the positions below in `table`
+    pub fn divide_with_table(
+        chess: &mut Chess,
+        depth: u8,
+        table: &mut TranspositionTable<u64>,
+    ) -> Perft {
+        Perft::divide_by(chess, depth, |chess, depth| {
+            transposition::perft(chess, depth, table)
+        })
+    }
+
+    fn divide_by(
+        chess: &mut Chess,
+        depth: u8,
+        mut count: impl FnMut(&mut Chess, u8) -> u64,
+    ) -> Perft {
+        let mut child_count = BTreeMap::new();
+
+        if depth > 0 {
+            for _move in chess.generate_legal_moves() {
+                chess.play_move(_move);
+                child_count.insert(_move.to_uci(), count(chess, depth - 1));
+                chess.undo();
+            }
+        }
+
+        let total_count = if depth == 0 {
+            1
+        } else {
+            child_count.values().sum()
+        };
+
+        Perft::new(total_count, child_count)
+    }
+
+    pub fn total_count(&self) -> u64 {
+        self.total_count
+    }
+
+    pub fn child_count(&self) -> &BTreeMap<String, u64> {
+        &self.child_count
+    }
+}
+
+/// Where `Chess` and the engine first disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// the FEN of the position
+    pub fen: String,
+    /// the moves from the starting position to this one, in UCI notation
+    pub moves: Vec<String>,
+    pub depth: u8,
+    pub chess: Perft,
+    pub engine: Perft,
+}
+
+impl Mismatch {
+    /// Legal moves according to the engine that `Chess` doesn't generate
+    pub fn missing_moves(&self) -> Vec<&str> {
+        only_in(&self.engine, &self.chess)
+    }
+
+    /// Moves generated by `Chess` that the engine doesn't consider legal
+    pub fn extra_moves(&self) -> Vec<&str> {
+        only_in(&self.chess, &self.engine)
+    }
+}
+
+fn only_in<'a>(perft: &'a Perft, other: &Perft) -> Vec<&'a str> {
+    perft
+        .child_count
+        .keys()
+        .filter(|uci| !other.child_count.contains_key(*uci))
+        .map(String::as_str)
+        .collect()
+}
+
+/// The divide counts of a position from `Chess` and from the engine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    pub chess: Perft,
+    pub engine: Perft,
+    /// the position the first move with a different count leads to, where the legal moves
+    /// differ, or `None` if the counts are the same
+    pub mismatch: Option<Mismatch>,
+}
+
+/// Compare the divide counts of the position of `chess` with the engine, and follow the first
+/// move with a different count until the lists of legal moves differ.
+///
+/// `chess` is left in the position it started from.
+pub fn compare(chess: &mut Chess, depth: u8, engine: &mut impl Engine) -> io::Result<Comparison> {
+    let fen = chess.get_fen();
+    let mut moves: Vec<String> = vec![];
+    let mut mismatch: Option<Mismatch> = None;
+    let mut comparison = None;
+
+    for depth in (1..=depth).rev() {
+        let ours = Perft::divide(chess, depth);
+        let theirs = engine.perft(&fen, &moves, depth)?;
+
+        if comparison.is_none() {
+            comparison = Some((ours.clone(), theirs.clone()));
+        }
+
+        // the engine can agree one ply deeper if it isn't consistent with itself, in which case
+        // the parent position is as far as we can go
+        if ours == theirs {
+            break;
+        }
+
+        let same_moves = ours.child_count.keys().eq(theirs.child_count.keys());
+        let next_move = ours
+            .child_count
+            .iter()
+            .find(|(uci, count)| theirs.child_count.get(*uci) != Some(count))
+            .map(|(uci, _)| uci.clone());
+
+        mismatch = Some(Mismatch {
+            fen: chess.get_fen(),
+            moves: moves.clone(),
+            depth,
+            chess: ours,
+            engine: theirs,
+        });
+
+        match next_move {
+            Some(uci) if same_moves && depth > 1 => {
+                chess
+                    .make_uci_move(&uci)
+                    .expect("the move was generated by Chess");
+                moves.push(uci);
+            }
+            _ => break,
+        }
+    }
+
+    for _ in &moves {
+        chess.undo();
+    }
+
+    // there is nothing to compare at depth 0, the position itself is the only node
+    let (chess, engine) = comparison.unwrap_or_else(|| {
+        let perft = Perft::divide(chess, 0);
+        (perft.clone(), perft)
+    });
+
+    Ok(Comparison {
+        chess,
+        engine,
+        mismatch,
+    })
+}
+
+/// An engine that speaks UCI over stdin and stdout, like Stockfish, with the `go perft`
+/// extension
+pub struct UciEngine {
+    child: Child,
+    inp: BufReader<ChildStdout>,
+    out: ChildStdin,
+}
+
+impl UciEngine {
+    /// Start the engine, e.g. `UciEngine::new("stockfish", &[])`, and wait until it is ready
+    pub fn new(program: &str, args: &[&str]) -> io::Result<UciEngine> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let inp = BufReader::new(child.stdout.take().expect("stdout not captured"));
+        let out = child.stdin.take().expect("stdin not captured");
+
+        let mut engine = UciEngine { child, inp, out };
+
+        engine.send("uci")?;
+        engine.wait_for("uciok")?;
+        engine.is_ready()?;
+
+        Ok(engine)
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> io::Result<()> {
+        self.send(&format!("setoption name {} value {}", name, value))?;
+        self.is_ready()
+    }
+
+    fn is_ready(&mut self) -> io::Result<()> {
+        self.send("isready")?;
+        self.wait_for("readyok")
+    }
+
+    fn send(&mut self, command: &str) -> io::Result<()> {
+        writeln!(self.out, "{}", command)?;
+        self.out.flush()
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+
+        if self.inp.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the engine exited",
+            ));
+        }
+
+        Ok(line.trim().to_string())
+    }
+
+    /// Skip lines until `expected`, such as the `id` and `option` lines before `uciok`
+    fn wait_for(&mut self, expected: &str) -> io::Result<()> {
+        while self.read_line()? != expected {}
+
+        Ok(())
+    }
+}
+
+impl Engine for UciEngine {
+    fn perft(&mut self, fen: &str, moves: &[String], depth: u8) -> io::Result<Perft> {
+        let mut position = format!("position fen {}", fen);
+
+        if !moves.is_empty() {
+            position.push_str(" moves ");
+            position.push_str(&moves.join(" "));
+        }
+
+        self.send(&position)?;
+        self.send(&format!("go perft {}", depth))?;
+
+        let mut child_count = BTreeMap::new();
+
+        // `e2e4: 20` for each move, then `Nodes searched: 400`. Anything else, like `info`
+        // lines, is skipped.
+        loop {
+            let line = self.read_line()?;
+
+            if let Some(total) = line.strip_prefix("Nodes searched:") {
+                let total_count = total.trim().parse().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid node count '{}'", line),
+                    )
+                })?;
+
+                return Ok(Perft::new(total_count, child_count));
+            }
+
+            let divide = line
+                .split_once(':')
+                .and_then(|(uci, count)| Some((uci.trim(), count.trim().parse().ok()?)));
+
+            if let Some((uci, count)) = divide {
+                child_count.insert(uci.to_string(), count);
+            }
+        }
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
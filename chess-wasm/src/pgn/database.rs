@@ -0,0 +1,252 @@
+use super::reader::{parse_headers, parse_movetext};
+use super::{Game, GameResult};
+use crate::errors::PgnError;
+use std::io::BufRead;
+
+type HeaderFilter = Box<dyn Fn(&str) -> bool>;
+
+/// A game read from a PGN database. Only the tag pairs are parsed, the movetext is kept as
+/// text until `game` is called.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameEntry {
+    /// the line of the file the game starts on
+    pub line: usize,
+    pub headers: Vec<(String, String)>,
+    movetext: String,
+    movetext_line: usize,
+}
+
+impl GameEntry {
+    /// Return the value of a tag, e.g. `entry.header("White")`
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn movetext(&self) -> &str {
+        &self.movetext
+    }
+
+    /// Parse the movetext. Syntax errors report the line in the file, not in the game.
+    pub fn game(&self) -> Result<Game, PgnError> {
+        parse_movetext(&self.movetext, self.movetext_line, self.headers.clone())
+    }
+}
+
+/// Read the games of a PGN database one at a time, so files of any size can be searched.
+///
+/// ```no_run
+/// use chess_wasm::pgn::PgnReader;
+/// use std::{fs::File, io::BufReader};
+///
+/// let file = BufReader::new(File::open("games.pgn").unwrap());
+///
+/// for entry in PgnReader::new(file).filter_header("White", |white| white == "Carlsen, Magnus") {
+///     match entry.and_then(|entry| entry.game()) {
+///         Ok(game) => println!("{}", game),
+///         Err(err) => eprintln!("{}", err),
+///     }
+/// }
+/// ```
+///
+/// Anything between the end of a game and the next tag pair is skipped. A game with invalid
+/// tag pairs is returned as an error and reading goes on with the next one, only I/O errors
+/// stop the iteration.
+pub struct PgnReader<R> {
+    reader: R,
+    /// the number of lines read so far
+    line: usize,
+    /// a tag pair that was read while looking for the end of the previous game
+    pending: Option<String>,
+    filters: Vec<(String, HeaderFilter)>,
+    done: bool,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: 0,
+            pending: None,
+            filters: vec![],
+            done: false,
+        }
+    }
+
+    /// Only return games that have the tag and whose value matches the predicate.
+    /// The movetext of other games is skipped without being stored.
+    pub fn filter_header(mut self, name: &str, predicate: impl Fn(&str) -> bool + 'static) -> Self {
+        self.filters.push((name.to_string(), Box::new(predicate)));
+        self
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>, PgnError> {
+        if let Some(line) = self.pending.take() {
+            return Ok(Some(line));
+        }
+
+        let mut buf = vec![];
+
+        match self.reader.read_until(b'\n', &mut buf) {
+            Ok(0) => Ok(None),
+            Ok(_) => {
+                self.line += 1;
+
+                // old databases are often Latin-1, which is not worth failing a game for
+                let line = String::from_utf8_lossy(&buf);
+                Ok(Some(line.trim_end_matches(['\n', '\r']).to_string()))
+            }
+            Err(err) => Err(PgnError::Io(err.to_string())),
+        }
+    }
+
+    fn matches(&self, headers: &[(String, String)]) -> bool {
+        self.filters.iter().all(|(name, predicate)| {
+            headers
+                .iter()
+                .find(|(tag, _)| tag == name)
+                .is_some_and(|(_, value)| predicate(value))
+        })
+    }
+
+    fn next_entry(&mut self) -> Result<Option<GameEntry>, PgnError> {
+        loop {
+            // skip everything up to the first tag pair
+            let first = loop {
+                match self.read_line()? {
+                    Some(line) if line.trim_start().starts_with('[') => break line,
+                    Some(_) => {}
+                    None => return Ok(None),
+                }
+            };
+
+            let first_line = self.line;
+            let mut header_text = first + "\n";
+
+            let mut next = self.read_line()?;
+
+            while let Some(line) = &next {
+                let trimmed = line.trim_start();
+
+                if !trimmed.is_empty() && !trimmed.starts_with('[') {
+                    break;
+                }
+
+                header_text.push_str(line);
+                header_text.push('\n');
+                next = self.read_line()?;
+            }
+
+            let headers = parse_headers(&header_text, first_line);
+            let keep = headers.as_ref().is_ok_and(|headers| self.matches(headers));
+
+            let movetext_line = self.line;
+            let mut movetext = String::new();
+            let mut scanner = Scanner::default();
+
+            while let Some(line) = next {
+                if !scanner.in_comment && line.trim_start().starts_with('[') {
+                    // the game had no result, this is the start of the next one
+                    self.pending = Some(line);
+                    break;
+                }
+
+                let end = scanner.scan(&line);
+
+                if keep {
+                    movetext.push_str(&line[..end.unwrap_or(line.len())]);
+                    movetext.push('\n');
+                }
+
+                if end.is_some() {
+                    break;
+                }
+
+                next = self.read_line()?;
+            }
+
+            let headers = headers?;
+
+            if keep {
+                return Ok(Some(GameEntry {
+                    line: first_line,
+                    headers,
+                    movetext,
+                    movetext_line,
+                }));
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<GameEntry, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_entry() {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = matches!(err, PgnError::Io(_));
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Find the game result in the movetext without parsing it, skipping comments and variations
+#[derive(Default)]
+struct Scanner {
+    in_comment: bool,
+    variation_depth: usize,
+}
+
+impl Scanner {
+    /// Scan the next line and return where the game result ends if it is on this line
+    fn scan(&mut self, line: &str) -> Option<usize> {
+        if line.starts_with('%') {
+            return None;
+        }
+
+        let mut word_start = None;
+
+        for (i, c) in line.char_indices().chain([(line.len(), ' ')]) {
+            if self.in_comment {
+                self.in_comment = c != '}';
+                continue;
+            }
+
+            let is_word = !c.is_whitespace() && !"{;()".contains(c);
+
+            if is_word {
+                word_start.get_or_insert(i);
+                continue;
+            }
+
+            if let Some(start) = word_start.take() {
+                if self.variation_depth == 0 && line[start..i].parse::<GameResult>().is_ok() {
+                    return Some(i);
+                }
+            }
+
+            match c {
+                '{' => self.in_comment = true,
+                ';' => return None,
+                '(' => self.variation_depth += 1,
+                ')' => self.variation_depth = self.variation_depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
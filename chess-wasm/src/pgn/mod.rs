@@ -0,0 +1,172 @@
+mod database;
+mod reader;
+mod writer;
+
+pub use database::*;
+pub use reader::*;
+
+use crate::chess::Chess;
+use crate::chess2::Color;
+use crate::errors::PgnError;
+use crate::outcome::Outcome;
+use std::fmt;
+use std::str::FromStr;
+
+pub const DEFAULT_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// The tags every PGN game is expected to have, in the order they are exported
+pub const SEVEN_TAG_ROSTER: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// the game is still going on or the result is unknown
+    Unknown,
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let result = match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Unknown => "*",
+        };
+
+        f.write_str(result)
+    }
+}
+
+impl From<Outcome> for GameResult {
+    fn from(outcome: Outcome) -> Self {
+        match outcome.winner {
+            Some(Color::White) => GameResult::WhiteWins,
+            Some(Color::Black) => GameResult::BlackWins,
+            None => GameResult::Draw,
+        }
+    }
+}
+
+impl FromStr for GameResult {
+    type Err = ();
+
+    fn from_str(result: &str) -> Result<Self, Self::Err> {
+        match result {
+            "1-0" => Ok(GameResult::WhiteWins),
+            "0-1" => Ok(GameResult::BlackWins),
+            "1/2-1/2" => Ok(GameResult::Draw),
+            "*" => Ok(GameResult::Unknown),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A move in the movetext together with its annotations and the alternatives to it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PgnMove {
+    pub san: String,
+    /// Numeric Annotation Glyphs, `!` and `?` suffixes are stored as their NAG ($1, $2, ...)
+    pub nags: Vec<u8>,
+    /// comments that follow the move
+    pub comments: Vec<String>,
+    /// lines that could have been played instead of this move
+    pub variations: Vec<Line>,
+}
+
+/// A sequence of moves, either the main line of a game or a variation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Line {
+    /// comments before the first move of the line
+    pub comments: Vec<String>,
+    pub moves: Vec<PgnMove>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    /// tag pairs in the order they appear
+    pub headers: Vec<(String, String)>,
+    pub moves: Line,
+    pub result: GameResult,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self {
+            headers: vec![],
+            moves: Line::default(),
+            result: GameResult::Unknown,
+        }
+    }
+}
+
+impl FromStr for Game {
+    type Err = PgnError;
+
+    fn from_str(pgn: &str) -> Result<Self, Self::Err> {
+        parse_game(pgn)
+    }
+}
+
+impl Game {
+    /// Return the value of a tag, e.g. `game.header("White")`
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The position the game starts from, taken from the `FEN` tag if there is one
+    pub fn starting_fen(&self) -> &str {
+        self.header("FEN").unwrap_or(DEFAULT_POSITION)
+    }
+
+    /// Chess960 games are marked with a `Variant` tag
+    pub fn is_chess960(&self) -> bool {
+        self.header("Variant").is_some_and(|variant| {
+            variant.eq_ignore_ascii_case("chess960") || variant.eq_ignore_ascii_case("fischerandom")
+        })
+    }
+
+    /// Load the starting position into `chess` and play the main line. Variations are checked
+    /// along the way, so any illegal move in the game is reported with the ply it was played at.
+    pub fn replay(&self, chess: &mut Chess) -> Result<(), PgnError> {
+        chess.set_chess960(self.is_chess960());
+        chess.try_load_fen(self.starting_fen())?;
+
+        play_line(chess, &self.moves, 1)
+    }
+}
+
+/// Play the moves of a line, checking its variations before each move.
+/// `ply` is the number of the first move of the line, counted from the start of the game.
+fn play_line(chess: &mut Chess, line: &Line, ply: usize) -> Result<(), PgnError> {
+    for (i, pgn_move) in line.moves.iter().enumerate() {
+        let ply = ply + i;
+
+        // a variation replaces the move, so it is played from the same position
+        for variation in &pgn_move.variations {
+            play_line(chess, variation, ply)?;
+
+            for _ in &variation.moves {
+                chess.undo();
+            }
+        }
+
+        let _move =
+            chess
+                .parse_san_lenient(&pgn_move.san)
+                .map_err(|error| PgnError::IllegalMove {
+                    ply,
+                    san: pgn_move.san.clone(),
+                    error,
+                })?;
+
+        chess.play_move(_move);
+    }
+
+    Ok(())
+}
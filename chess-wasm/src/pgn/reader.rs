@@ -0,0 +1,323 @@
+use super::{Game, GameResult, Line, PgnMove};
+use crate::errors::PgnError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    TagOpen,
+    TagClose,
+    String(String),
+    /// move numbers, SAN moves and tag names
+    Symbol(String),
+    Period,
+    Comment(String),
+    Nag(u8),
+    VariationStart,
+    VariationEnd,
+    Result(GameResult),
+}
+
+/// Parse a single game in Portable Game Notation
+///
+/// http://www.saremba.de/chessgml/standards/pgn/pgn-complete.htm
+pub fn parse_game(pgn: &str) -> Result<Game, PgnError> {
+    let mut parser = Parser::new(pgn, 1)?;
+    let headers = parser.parse_headers()?;
+
+    parser.parse_game(headers)
+}
+
+/// Parse the tag pairs of a game, `first_line` is the line they start on in the file
+pub(crate) fn parse_headers(
+    text: &str,
+    first_line: usize,
+) -> Result<Vec<(String, String)>, PgnError> {
+    let mut parser = Parser::new(text, first_line)?;
+    let headers = parser.parse_headers()?;
+
+    if let Some((_, line)) = parser.tokens.get(parser.pos) {
+        return Err(syntax_error(*line, "expected a tag pair"));
+    }
+
+    Ok(headers)
+}
+
+/// Parse the movetext of a game whose tag pairs have already been read
+pub(crate) fn parse_movetext(
+    text: &str,
+    first_line: usize,
+    headers: Vec<(String, String)>,
+) -> Result<Game, PgnError> {
+    Parser::new(text, first_line)?.parse_game(headers)
+}
+
+fn syntax_error(line: usize, message: &str) -> PgnError {
+    PgnError::Syntax {
+        line,
+        message: message.to_string(),
+    }
+}
+
+struct Parser {
+    /// every token with the line it starts on
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    first_line: usize,
+}
+
+impl Parser {
+    fn new(pgn: &str, first_line: usize) -> Result<Self, PgnError> {
+        Ok(Parser {
+            tokens: tokenize(pgn, first_line)?,
+            pos: 0,
+            first_line,
+        })
+    }
+
+    fn next(&mut self) -> Option<(Token, usize)> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn last_line(&self) -> usize {
+        self.tokens
+            .last()
+            .map_or(self.first_line, |(_, line)| *line)
+    }
+
+    fn parse_headers(&mut self) -> Result<Vec<(String, String)>, PgnError> {
+        let mut headers = vec![];
+
+        while let Some((Token::TagOpen, line)) = self.tokens.get(self.pos) {
+            let line = *line;
+            self.pos += 1;
+
+            let name = match self.next() {
+                Some((Token::Symbol(name), _)) => name,
+                _ => return Err(syntax_error(line, "expected a tag name")),
+            };
+
+            let value = match self.next() {
+                Some((Token::String(value), _)) => value,
+                _ => return Err(syntax_error(line, "expected a tag value")),
+            };
+
+            if !matches!(self.next(), Some((Token::TagClose, _))) {
+                return Err(syntax_error(line, "expected ']' after the tag value"));
+            }
+
+            headers.push((name, value));
+        }
+
+        Ok(headers)
+    }
+
+    fn parse_game(&mut self, headers: Vec<(String, String)>) -> Result<Game, PgnError> {
+        let mut game = Game {
+            headers,
+            ..Game::default()
+        };
+
+        let (moves, result) = self.parse_line(false)?;
+
+        if let Some((_, line)) = self.tokens.get(self.pos) {
+            return Err(syntax_error(
+                *line,
+                "unexpected token after the game result",
+            ));
+        }
+
+        game.moves = moves;
+        game.result = result
+            .or_else(|| game.header("Result").and_then(|r| r.parse().ok()))
+            .unwrap_or(GameResult::Unknown);
+
+        Ok(game)
+    }
+
+    /// Parse moves until the end of a variation, or the game result for the main line
+    fn parse_line(&mut self, is_variation: bool) -> Result<(Line, Option<GameResult>), PgnError> {
+        let mut line = Line::default();
+
+        while let Some((token, line_number)) = self.next() {
+            match token {
+                // move numbers
+                Token::Symbol(symbol) if symbol.chars().all(|c| c.is_ascii_digit()) => {}
+                Token::Period => {}
+                Token::Symbol(san) => line.moves.push(PgnMove {
+                    san,
+                    ..PgnMove::default()
+                }),
+                Token::Nag(nag) => match line.moves.last_mut() {
+                    Some(last) => last.nags.push(nag),
+                    None => return Err(syntax_error(line_number, "annotation before any move")),
+                },
+                Token::Comment(comment) => match line.moves.last_mut() {
+                    Some(last) => last.comments.push(comment),
+                    None => line.comments.push(comment),
+                },
+                Token::VariationStart => {
+                    let (variation, _) = self.parse_line(true)?;
+
+                    match line.moves.last_mut() {
+                        Some(last) => last.variations.push(variation),
+                        None => return Err(syntax_error(line_number, "variation before any move")),
+                    }
+                }
+                Token::VariationEnd if is_variation => return Ok((line, None)),
+                Token::VariationEnd => return Err(syntax_error(line_number, "unmatched ')'")),
+                Token::Result(_) if is_variation => {
+                    return Err(syntax_error(line_number, "game result inside a variation"))
+                }
+                Token::Result(result) => return Ok((line, Some(result))),
+                Token::TagOpen | Token::TagClose | Token::String(_) => {
+                    return Err(syntax_error(line_number, "unexpected tag in the movetext"))
+                }
+            }
+        }
+
+        if is_variation {
+            return Err(syntax_error(self.last_line(), "unterminated variation"));
+        }
+
+        Ok((line, None))
+    }
+}
+
+fn tokenize(pgn: &str, first_line: usize) -> Result<Vec<(Token, usize)>, PgnError> {
+    let chars: Vec<char> = pgn.chars().collect();
+    let mut tokens = vec![];
+    let mut line = first_line;
+    let mut i = 0;
+
+    // read characters while the predicate holds, counting new lines
+    let take_while = |i: &mut usize, line: &mut usize, predicate: &dyn Fn(char) -> bool| {
+        let start = *i;
+
+        while *i < chars.len() && predicate(chars[*i]) {
+            if chars[*i] == '\n' {
+                *line += 1;
+            }
+
+            *i += 1;
+        }
+
+        chars[start..*i].iter().collect::<String>()
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start_line = line;
+
+        let token = match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            // escape mechanism, the whole line is ignored
+            '%' if i == 0 || chars[i - 1] == '\n' => {
+                take_while(&mut i, &mut line, &|c| c != '\n');
+                continue;
+            }
+            ';' => {
+                i += 1;
+                let comment = take_while(&mut i, &mut line, &|c| c != '\n');
+                Token::Comment(comment.trim().to_string())
+            }
+            '{' => {
+                i += 1;
+                let comment = take_while(&mut i, &mut line, &|c| c != '}');
+
+                if i == chars.len() {
+                    return Err(syntax_error(start_line, "unterminated comment"));
+                }
+
+                i += 1;
+                Token::Comment(comment.trim().to_string())
+            }
+            '"' => {
+                i += 1;
+                let mut value = String::new();
+
+                loop {
+                    match chars.get(i) {
+                        Some('\\') if matches!(chars.get(i + 1), Some('"' | '\\')) => {
+                            value.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some('"') => break,
+                        Some('\n') | None => {
+                            return Err(syntax_error(start_line, "unterminated string"))
+                        }
+                        Some(&c) => {
+                            value.push(c);
+                            i += 1;
+                        }
+                    }
+                }
+
+                i += 1;
+                Token::String(value)
+            }
+            '$' => {
+                i += 1;
+                let nag = take_while(&mut i, &mut line, &|c| c.is_ascii_digit());
+
+                match nag.parse() {
+                    Ok(nag) => Token::Nag(nag),
+                    Err(_) => return Err(syntax_error(start_line, "invalid annotation glyph")),
+                }
+            }
+            '!' | '?' => {
+                let suffix = take_while(&mut i, &mut line, &|c| c == '!' || c == '?');
+
+                match suffix.as_str() {
+                    "!" => Token::Nag(1),
+                    "?" => Token::Nag(2),
+                    "!!" => Token::Nag(3),
+                    "??" => Token::Nag(4),
+                    "!?" => Token::Nag(5),
+                    "?!" => Token::Nag(6),
+                    _ => return Err(syntax_error(start_line, "invalid move annotation")),
+                }
+            }
+            '[' | ']' | '(' | ')' | '.' | '*' => {
+                i += 1;
+
+                match c {
+                    '[' => Token::TagOpen,
+                    ']' => Token::TagClose,
+                    '(' => Token::VariationStart,
+                    ')' => Token::VariationEnd,
+                    '.' => Token::Period,
+                    _ => Token::Result(GameResult::Unknown),
+                }
+            }
+            c if c.is_ascii_alphanumeric() => {
+                let symbol = take_while(&mut i, &mut line, &|c| {
+                    c.is_ascii_alphanumeric() || "_+#=:-/".contains(c)
+                });
+
+                match symbol.parse() {
+                    Ok(result) => Token::Result(result),
+                    Err(_) => Token::Symbol(symbol),
+                }
+            }
+            c => {
+                return Err(syntax_error(
+                    start_line,
+                    &format!("unexpected character '{}'", c),
+                ))
+            }
+        };
+
+        tokens.push((token, start_line));
+    }
+
+    Ok(tokens)
+}
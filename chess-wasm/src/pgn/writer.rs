@@ -0,0 +1,189 @@
+use super::{Game, GameResult, Line, PgnMove, DEFAULT_POSITION, SEVEN_TAG_ROSTER};
+use crate::chess::Chess;
+use std::fmt;
+
+/// Export format lines are at most 80 characters long
+const MAX_LINE_LENGTH: usize = 80;
+
+impl Chess {
+    /// Export the moves played since the position was loaded as a PGN game.
+    ///
+    /// The seven tag roster is always written, using `tags` where given and `?` otherwise,
+    /// followed by the `SetUp` and `FEN` tags for games that don't start from the standard
+    /// position and then the remaining `tags` in order.
+    pub fn pgn(&self, tags: &[(&str, &str)]) -> String {
+        self.to_game(tags).to_string()
+    }
+
+    /// Build a `Game` from the move history, see `pgn`.
+    ///
+    /// The result comes from `outcome` if the game is over, and is a draw if one could be
+    /// claimed by threefold repetition or the fifty-move rule. Otherwise the `Result` tag in
+    /// `tags` is used, for games that ended in a way `Chess` doesn't know about.
+    ///
+    /// A game where no position was ever loaded starts from the standard position.
+    pub fn to_game(&self, tags: &[(&str, &str)]) -> Game {
+        let starting_fen = match self.starting_fen() {
+            "" => DEFAULT_POSITION,
+            fen => fen,
+        };
+        let history = self.history();
+        let mut chess = Chess::new();
+        let mut moves = Line::default();
+
+        chess.set_chess960(self.is_chess960());
+        chess.load_fen(starting_fen.to_string());
+
+        for _move in history {
+            moves.moves.push(PgnMove {
+                san: chess.san(_move),
+                ..PgnMove::default()
+            });
+
+            chess.play_move(_move);
+        }
+
+        let result = match self.outcome() {
+            Some(outcome) => GameResult::from(outcome),
+            None if self.can_claim_draw() => GameResult::Draw,
+            None => tags
+                .iter()
+                .find(|(name, _)| *name == "Result")
+                .and_then(|(_, value)| value.parse().ok())
+                .unwrap_or(GameResult::Unknown),
+        };
+
+        let tag = |name: &str| tags.iter().find(|(tag, _)| *tag == name).map(|(_, v)| *v);
+
+        let mut headers: Vec<(String, String)> = SEVEN_TAG_ROSTER
+            .iter()
+            .map(|&name| {
+                let value = match name {
+                    "Result" => result.to_string(),
+                    "Date" => tag(name).unwrap_or("????.??.??").to_string(),
+                    _ => tag(name).unwrap_or("?").to_string(),
+                };
+
+                (name.to_string(), value)
+            })
+            .collect();
+
+        if starting_fen != DEFAULT_POSITION {
+            headers.push(("SetUp".to_string(), "1".to_string()));
+            headers.push(("FEN".to_string(), starting_fen.to_string()));
+        }
+
+        if self.is_chess960() {
+            headers.push(("Variant".to_string(), "Chess960".to_string()));
+        }
+
+        for (name, value) in tags {
+            let is_written = SEVEN_TAG_ROSTER.contains(name)
+                || *name == "SetUp"
+                || *name == "FEN"
+                || (*name == "Variant" && self.is_chess960());
+
+            if !is_written {
+                headers.push((name.to_string(), value.to_string()));
+            }
+        }
+
+        Game {
+            headers,
+            moves,
+            result,
+        }
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in &self.headers {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{} \"{}\"]", name, value)?;
+        }
+
+        if !self.headers.is_empty() {
+            writeln!(f)?;
+        }
+
+        let mut tokens = vec![];
+        line_tokens(&self.moves, first_ply(self.starting_fen()), &mut tokens);
+        tokens.push(self.result.to_string());
+
+        let mut line = String::new();
+
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > MAX_LINE_LENGTH {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+
+            if !line.is_empty() {
+                line.push(' ');
+            }
+
+            line.push_str(&token);
+        }
+
+        writeln!(f, "{}", line)
+    }
+}
+
+/// The number of half moves played before the position, where `1. e4` is ply 0
+fn first_ply(fen: &str) -> usize {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+
+    let full_moves: usize = fields.get(5).and_then(|n| n.parse().ok()).unwrap_or(1);
+    let black_to_move = fields.get(1) == Some(&"b");
+
+    full_moves.max(1) * 2 - 2 + black_to_move as usize
+}
+
+/// Split a line into the tokens of the movetext. Black moves get their own move number at the
+/// start of the line and after comments or variations, e.g. `1. e4 {best by test} 1... e5`.
+fn line_tokens(line: &Line, ply: usize, tokens: &mut Vec<String>) {
+    for comment in &line.comments {
+        tokens.push(format!("{{{}}}", comment));
+    }
+
+    let mut needs_number = true;
+
+    for (i, pgn_move) in line.moves.iter().enumerate() {
+        let ply = ply + i;
+        let number = ply / 2 + 1;
+
+        if ply.is_multiple_of(2) {
+            tokens.push(format!("{}.", number));
+        } else if needs_number {
+            tokens.push(format!("{}...", number));
+        }
+
+        tokens.push(pgn_move.san.clone());
+
+        for nag in &pgn_move.nags {
+            tokens.push(format!("${}", nag));
+        }
+
+        for comment in &pgn_move.comments {
+            tokens.push(format!("{{{}}}", comment));
+        }
+
+        for variation in &pgn_move.variations {
+            let mut variation_tokens = vec![];
+            line_tokens(variation, ply, &mut variation_tokens);
+
+            if let Some(first) = variation_tokens.first_mut() {
+                first.insert(0, '(');
+            }
+
+            if let Some(last) = variation_tokens.last_mut() {
+                last.push(')');
+            }
+
+            tokens.append(&mut variation_tokens);
+        }
+
+        needs_number = !pgn_move.comments.is_empty() || !pgn_move.variations.is_empty();
+    }
+}
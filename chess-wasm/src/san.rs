@@ -0,0 +1,367 @@
+use crate::chess::Piece::*;
+use crate::chess::*;
+use crate::errors::MoveError;
+
+/// A move notation split into its parts, before it is matched against the legal moves
+#[derive(Debug, Clone, Copy)]
+enum ParsedSan {
+    Castle {
+        king_side: bool,
+    },
+    Normal {
+        /// `PAWN`, `KNIGHT`, ... without color
+        piece_type: PieceType,
+        from_file: Option<u8>,
+        from_rank: Option<u8>,
+        capture: bool,
+        to: PieceIndex,
+        promotion: Option<PieceType>,
+    },
+}
+
+// https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+impl Chess {
+    /// Return the Standard Algebraic Notation of a legal move in the current position,
+    /// for example `Nbd2`, `exd6`, `e8=Q+` or `O-O-O#`
+    pub fn san(&mut self, _move: Move) -> String {
+        let mut san = if _move.flags & MoveFlag::KING_SIDE_CASTLE != 0 {
+            String::from("O-O")
+        } else if _move.flags & MoveFlag::QUEEN_SIDE_CASTLE != 0 {
+            String::from("O-O-O")
+        } else {
+            self.san_without_suffix(_move)
+        };
+
+        san.push_str(self.check_suffix(_move));
+
+        san
+    }
+
+    /// Find the legal move written in Standard Algebraic Notation.
+    ///
+    /// Check and checkmate suffixes, annotations like `!?` and castling with zeros are accepted,
+    /// but the notation is otherwise expected to be well formed.
+    pub fn parse_san(&mut self, san: &str) -> Result<Move, MoveError> {
+        let parsed = parse(san, false)?;
+
+        self.resolve_san(san, &parsed, false)
+    }
+
+    /// Like `parse_san`, but also accepts the sloppy notation found in third-party PGNs:
+    /// lowercase piece letters, promotions without `=` (`e8Q`), missing promotion pieces
+    /// (promoting to a queen), long algebraic notation (`Ng1-f3`) and `e.p.` suffixes.
+    pub fn parse_san_lenient(&mut self, san: &str) -> Result<Move, MoveError> {
+        let parsed = parse(san, true)?;
+
+        self.resolve_san(san, &parsed, true)
+    }
+
+    /// Match the parsed notation against the legal moves. A lowercase `b` can be either a pawn
+    /// or a bishop in lenient mode, so the candidates are tried in order.
+    fn resolve_san(
+        &mut self,
+        san: &str,
+        candidates: &[ParsedSan],
+        lenient: bool,
+    ) -> Result<Move, MoveError> {
+        let legal_moves = self.generate_legal_moves();
+        let mut error = MoveError::InvalidNotation(san.to_string());
+
+        for parsed in candidates {
+            match find_move(&legal_moves, parsed, lenient) {
+                Ok(_move) => return Ok(_move),
+                Err(err) => error = err,
+            }
+        }
+
+        Err(error)
+    }
+
+    fn san_without_suffix(&mut self, _move: Move) -> String {
+        let mut san = String::new();
+        let from = self.convert_index_algebraic_notation(_move.from);
+
+        match _move.piece & !BLACK {
+            PAWN => {
+                // pawn captures are always disambiguated by their file
+                if _move.is_capture() {
+                    san.push_str(&from[..1]);
+                }
+            }
+            piece_type => {
+                san.push(role_letter(piece_type));
+                san.push_str(&self.disambiguation(_move));
+            }
+        }
+
+        if _move.is_capture() {
+            san.push('x');
+        }
+
+        san.push_str(&self.convert_index_algebraic_notation(_move.to));
+
+        if let Some(promotion) = _move.promotion {
+            san.push('=');
+            san.push(role_letter(promotion & !BLACK));
+        }
+
+        san
+    }
+
+    /// The file, rank or square of the moving piece if another piece of the same type can
+    /// move to the same square
+    fn disambiguation(&mut self, _move: Move) -> String {
+        let from = self.convert_index_algebraic_notation(_move.from);
+
+        let others: Vec<Move> = self
+            .generate_legal_moves()
+            .into_iter()
+            .filter(|m| m.piece == _move.piece && m.to == _move.to && m.from != _move.from)
+            .collect();
+
+        if others.is_empty() {
+            return String::new();
+        }
+
+        // squares on the same file share the lower 3 bits, squares on the same rank the upper 4
+        let same_file = others.iter().any(|m| m.from & 7 == _move.from & 7);
+        let same_rank = others.iter().any(|m| m.from >> 4 == _move.from >> 4);
+
+        if !same_file {
+            from[..1].to_string()
+        } else if !same_rank {
+            from[1..].to_string()
+        } else {
+            from
+        }
+    }
+
+    /// `#` if the move checkmates, `+` if it checks, nothing otherwise
+    fn check_suffix(&mut self, _move: Move) -> &'static str {
+        self.play_move(_move);
+
+        let suffix = if !self.in_check() {
+            ""
+        } else if self.generate_legal_moves().is_empty() {
+            "#"
+        } else {
+            "+"
+        };
+
+        self.undo();
+
+        suffix
+    }
+}
+
+fn role_letter(piece_type: PieceType) -> char {
+    match piece_type {
+        KING => 'K',
+        QUEEN => 'Q',
+        ROOK => 'R',
+        BISHOP => 'B',
+        KNIGHT => 'N',
+        _ => panic!("pawns don't have a letter"),
+    }
+}
+
+fn find_move(legal_moves: &[Move], parsed: &ParsedSan, lenient: bool) -> Result<Move, MoveError> {
+    let (piece_type, from_file, from_rank, capture, to, promotion) = match *parsed {
+        ParsedSan::Castle { king_side } => {
+            let (flag, error) = if king_side {
+                (MoveFlag::KING_SIDE_CASTLE, MoveError::IllegalKingSideCastle)
+            } else {
+                (
+                    MoveFlag::QUEEN_SIDE_CASTLE,
+                    MoveError::IllegalQueenSideCastle,
+                )
+            };
+
+            return legal_moves
+                .iter()
+                .find(|m| m.flags & flag != 0)
+                .copied()
+                .ok_or(error);
+        }
+        ParsedSan::Normal {
+            piece_type,
+            from_file,
+            from_rank,
+            capture,
+            to,
+            promotion,
+        } => (piece_type, from_file, from_rank, capture, to, promotion),
+    };
+
+    let rank = 8 - (to >> 4);
+    let is_promotion_square = piece_type == PAWN && (rank == 1 || rank == 8);
+
+    if promotion.is_some() && !is_promotion_square {
+        return Err(MoveError::InvalidPromotion);
+    }
+
+    // lenient notation may leave out the piece, in which case we promote to a queen
+    let promotion = match promotion {
+        None if is_promotion_square && lenient => Some(QUEEN),
+        None if is_promotion_square => return Err(MoveError::InvalidPromotion),
+        promotion => promotion,
+    };
+
+    let mut matches = legal_moves.iter().filter(|m| {
+        !m.is_castle()
+            && m.piece & !BLACK == piece_type
+            && m.to == to
+            && from_file.is_none_or(|file| m.from & 7 == file)
+            && from_rank.is_none_or(|rank| 8 - (m.from >> 4) == rank)
+            && m.promotion.map(|p| p & !BLACK) == promotion
+    });
+
+    let _move = *matches.next().ok_or(MoveError::IllegalMove)?;
+
+    if matches.next().is_some() {
+        return Err(MoveError::AmbiguousMoveNotation);
+    }
+
+    if capture && !_move.is_capture() && !lenient {
+        return Err(MoveError::IllegalCapture);
+    }
+
+    Ok(_move)
+}
+
+/// Split a move notation into its parts. Lenient notation can have more than one meaning,
+/// so every possible reading is returned.
+fn parse(san: &str, lenient: bool) -> Result<Vec<ParsedSan>, MoveError> {
+    let invalid = || MoveError::InvalidNotation(san.to_string());
+
+    let mut text = san.trim();
+
+    if lenient {
+        text = text.trim_end_matches("e.p.").trim_end();
+    }
+
+    let text = text
+        .trim_end_matches(['!', '?'])
+        .trim_end_matches(['+', '#'])
+        .trim_end_matches(['!', '?']);
+
+    match text {
+        "O-O" | "0-0" => return Ok(vec![ParsedSan::Castle { king_side: true }]),
+        "O-O-O" | "0-0-0" => return Ok(vec![ParsedSan::Castle { king_side: false }]),
+        "o-o" if lenient => return Ok(vec![ParsedSan::Castle { king_side: true }]),
+        "o-o-o" if lenient => return Ok(vec![ParsedSan::Castle { king_side: false }]),
+        _ => {}
+    }
+
+    let mut chars: Vec<char> = text.chars().collect();
+
+    // promotion, e.g. e8=Q, or e8Q in lenient mode
+    let mut promotion = None;
+
+    if let Some(piece_type) = chars.last().and_then(|&c| piece_type(c, lenient)) {
+        chars.pop();
+
+        if chars.last() == Some(&'=') {
+            chars.pop();
+        } else if !lenient {
+            return Err(invalid());
+        }
+
+        promotion = Some(piece_type);
+    }
+
+    // destination square
+    let rank = chars.pop().and_then(rank_number).ok_or_else(invalid)?;
+    let file = chars.pop().and_then(file_number).ok_or_else(invalid)?;
+    let to = (8 - rank) * 16 + file;
+
+    let capture = chars.last() == Some(&'x') || (lenient && chars.last() == Some(&':'));
+
+    if capture || (lenient && chars.last() == Some(&'-')) {
+        chars.pop();
+    }
+
+    let mut readings = vec![];
+
+    // a lowercase b is either a pawn on the b-file or a bishop
+    let piece_readings = match chars.first() {
+        Some('b') if lenient => vec![(0, PAWN), (1, BISHOP)],
+        Some(&c) => match piece_type(c, lenient).or_else(|| king_type(c, lenient)) {
+            Some(piece_type) => vec![(1, piece_type)],
+            None => vec![(0, PAWN)],
+        },
+        None => vec![(0, PAWN)],
+    };
+
+    for (skip, piece_type) in piece_readings {
+        let mut rest = chars[skip..].iter().copied().peekable();
+
+        let from_file = rest
+            .next_if(|&c| file_number(c).is_some())
+            .and_then(file_number);
+        let from_rank = rest
+            .next_if(|&c| rank_number(c).is_some())
+            .and_then(rank_number);
+
+        if rest.next().is_some() {
+            continue;
+        }
+
+        // pawn captures must say which file the pawn comes from
+        if piece_type == PAWN && capture && from_file.is_none() {
+            continue;
+        }
+
+        readings.push(ParsedSan::Normal {
+            piece_type,
+            from_file,
+            from_rank,
+            capture,
+            to,
+            promotion,
+        });
+    }
+
+    if readings.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(readings)
+}
+
+/// Pieces a pawn can promote to. Lowercase letters are only accepted in lenient mode, and a
+/// lowercase b is never a piece here because it is also a file.
+fn piece_type(c: char, lenient: bool) -> Option<PieceType> {
+    match c {
+        'Q' => Some(QUEEN),
+        'R' => Some(ROOK),
+        'B' => Some(BISHOP),
+        'N' => Some(KNIGHT),
+        'q' if lenient => Some(QUEEN),
+        'r' if lenient => Some(ROOK),
+        'n' if lenient => Some(KNIGHT),
+        _ => None,
+    }
+}
+
+fn king_type(c: char, lenient: bool) -> Option<PieceType> {
+    match c {
+        'K' => Some(KING),
+        'k' if lenient => Some(KING),
+        _ => None,
+    }
+}
+
+fn file_number(c: char) -> Option<u8> {
+    match c {
+        'a'..='h' => Some(c as u8 - b'a'),
+        _ => None,
+    }
+}
+
+fn rank_number(c: char) -> Option<u8> {
+    match c {
+        '1'..='8' => Some(c as u8 - b'0'),
+        _ => None,
+    }
+}
@@ -0,0 +1,531 @@
+//! Negamax alpha-beta search with iterative deepening.
+//!
+//! Each iteration searches one ply deeper than the last, trying the principal variation of the
+//! previous iteration first so it cuts off sooner, then the other moves in the order of a
+//! `MovePicker`. At the horizon, captures are searched until the position is quiet, so a move
+//! isn't judged in the middle of an exchange.
+//!
+//! Scores are in centipawns from the point of view of the player to move. Checkmate is `MATE`
+//! minus the number of plies to it, so a quicker mate always scores higher.
+
+use crate::chess::{Chess, Move};
+use crate::eval::evaluate;
+use crate::move_picker::{is_tactical, History, MovePicker};
+use crate::transposition::{Bound, SearchEntry, TranspositionTable};
+use crate::utils::Clock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The score of being checkmated at the root, mates further away score less
+pub const MATE: i32 = 30_000;
+
+/// The deepest the search goes, counting the quiescence search
+pub const MAX_PLY: usize = 128;
+
+/// Larger than any score
+const INFINITY: i32 = MATE + 1;
+
+/// Scores beyond this are mates
+pub const MATE_BOUND: i32 = MATE - MAX_PLY as i32;
+
+/// How often the clock is read, in nodes
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// When to stop searching. The search stops at the first limit it reaches, without any limit
+/// it goes on until `MAX_PLY` or a forced mate is found.
+///
+/// The first iteration is always completed, so there is a move to play even if a limit is
+/// reached before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    /// in plies
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u8) -> SearchLimits {
+        SearchLimits {
+            depth: Some(depth),
+            ..SearchLimits::default()
+        }
+    }
+
+    pub fn nodes(nodes: u64) -> SearchLimits {
+        SearchLimits {
+            nodes: Some(nodes),
+            ..SearchLimits::default()
+        }
+    }
+
+    pub fn movetime(movetime: Duration) -> SearchLimits {
+        SearchLimits {
+            movetime: Some(movetime),
+            ..SearchLimits::default()
+        }
+    }
+}
+
+/// The outcome of the last completed iteration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// `None` if there is no legal move
+    pub best_move: Option<Move>,
+    pub score: i32,
+    /// the depth of the last completed iteration
+    pub depth: u8,
+    /// the nodes searched by all the iterations, including the one that was stopped
+    pub nodes: u64,
+    pub time: Duration,
+    /// the moves both players are expected to play, starting with `best_move`
+    pub pv: Vec<Move>,
+    /// 1 for the best line, 2 for the second best with `set_multi_pv`, and so on
+    pub multipv: usize,
+    /// how full the transposition table is, in permille
+    pub hashfull: u16,
+}
+
+impl SearchResult {
+    /// The number of moves until checkmate, negative if the player to move is mated
+    pub fn mate_in(&self) -> Option<i32> {
+        mate_in(self.score)
+    }
+}
+
+/// The number of moves until checkmate for a score, negative if the player to move is mated
+pub fn mate_in(score: i32) -> Option<i32> {
+    if score > MATE_BOUND {
+        Some((MATE - score + 1) / 2)
+    } else if score < -MATE_BOUND {
+        Some(-(MATE + score) / 2)
+    } else {
+        None
+    }
+}
+
+/// Search the position of `chess` until one of the limits is reached.
+///
+/// `chess` is left in the position it started from.
+pub fn search(chess: &mut Chess, limits: SearchLimits) -> SearchResult {
+    Search::new(limits).run(chess)
+}
+
+pub struct Search {
+    limits: SearchLimits,
+    clock: Clock,
+    nodes: u64,
+    stopped: bool,
+    /// whether a limit may stop the search, not until the first iteration is completed
+    can_stop: bool,
+    /// the principal variation from each ply, `pv[0]` is the one from the root
+    pv: Vec<Vec<Move>>,
+    /// the principal variation of the previous iteration, searched first
+    previous_pv: Vec<Move>,
+    /// the number of best lines to search
+    multi_pv: usize,
+    /// the first moves of the lines already found in this iteration, left out at the root
+    excluded: Vec<Move>,
+    /// the moves played from the root to the current position
+    line: Vec<Move>,
+    table: TranspositionTable,
+    history: History,
+    stop: Arc<AtomicBool>,
+}
+
+impl Search {
+    /// A search with a transposition table of the default size
+    pub fn new(limits: SearchLimits) -> Search {
+        Search::with_table(limits, TranspositionTable::default())
+    }
+
+    pub fn with_table(limits: SearchLimits, table: TranspositionTable) -> Search {
+        Search {
+            limits,
+            clock: Clock::start(),
+            nodes: 0,
+            stopped: false,
+            can_stop: false,
+            pv: vec![vec![]; MAX_PLY + 1],
+            previous_pv: vec![],
+            multi_pv: 1,
+            excluded: vec![],
+            line: vec![],
+            table,
+            history: History::new(),
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.limits = limits;
+    }
+
+    /// The transposition table, kept from one search to the next
+    pub fn table(&self) -> &TranspositionTable {
+        &self.table
+    }
+
+    pub fn table_mut(&mut self) -> &mut TranspositionTable {
+        &mut self.table
+    }
+
+    /// The quiet moves that caused cutoffs, kept from one search to the next like the table
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn history_mut(&mut self) -> &mut History {
+        &mut self.history
+    }
+
+    /// Search the `lines` best moves instead of only the best one, each iteration then
+    /// searches the position once per line, leaving out the first moves of the better lines
+    pub fn set_multi_pv(&mut self, lines: usize) {
+        self.multi_pv = lines.max(1);
+    }
+
+    /// A flag that stops the search once set, from another thread. It isn't cleared when a
+    /// search starts, so a stop sent before can't be missed.
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    /// Search one ply deeper at each iteration until a limit is reached
+    pub fn run(&mut self, chess: &mut Chess) -> SearchResult {
+        self.run_with(chess, |_| {})
+    }
+
+    /// Like `run`, calling `report` with each line as soon as it is searched, so the
+    /// progress can be shown
+    pub fn run_with(
+        &mut self,
+        chess: &mut Chess,
+        mut report: impl FnMut(&SearchResult),
+    ) -> SearchResult {
+        self.clock = Clock::start();
+        self.nodes = 0;
+        self.stopped = false;
+        self.can_stop = false;
+        self.previous_pv.clear();
+        self.line.clear();
+        self.table.new_search();
+        self.history.new_search();
+
+        let max_depth = self
+            .limits
+            .depth
+            .unwrap_or(u8::MAX)
+            .clamp(1, MAX_PLY as u8 - 1);
+
+        let lines = self.multi_pv.min(chess.generate_legal_moves().len()).max(1);
+
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            time: Duration::ZERO,
+            pv: vec![],
+            multipv: 1,
+            hashfull: 0,
+        };
+
+        'deepening: for depth in 1..=max_depth {
+            self.excluded.clear();
+
+            for line in 1..=lines {
+                let score = self.negamax(chess, depth as i32, 0, -INFINITY, INFINITY);
+
+                if self.stopped {
+                    break 'deepening;
+                }
+
+                let pv = self.pv[0].clone();
+                let line_result = SearchResult {
+                    best_move: pv.first().copied(),
+                    score,
+                    depth,
+                    nodes: self.nodes,
+                    time: self.clock.elapsed(),
+                    pv,
+                    multipv: line,
+                    hashfull: self.table.hashfull(),
+                };
+
+                report(&line_result);
+                self.excluded.extend(line_result.best_move);
+
+                if line == 1 {
+                    self.previous_pv = line_result.pv.clone();
+                    result = line_result;
+                }
+            }
+
+            self.can_stop = true;
+
+            // nothing to search, or a deeper search can't find a quicker mate
+            if result.best_move.is_none() || MATE - result.score.abs() <= depth as i32 {
+                break;
+            }
+        }
+
+        result.nodes = self.nodes;
+        result.time = self.clock.elapsed();
+        result.hashfull = self.table.hashfull();
+        result
+    }
+
+    fn negamax(&mut self, chess: &mut Chess, depth: i32, ply: usize, alpha: i32, beta: i32) -> i32 {
+        self.pv[ply].clear();
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        self.nodes += 1;
+
+        if ply > 0 && is_draw(chess) {
+            return 0;
+        }
+
+        if ply >= MAX_PLY {
+            return evaluate(chess);
+        }
+
+        // no line from here can be better than mating on the next move, or worse than being
+        // mated now
+        let alpha = alpha.max(-MATE + ply as i32);
+        let beta = beta.min(MATE - ply as i32 - 1);
+
+        if alpha >= beta {
+            return alpha;
+        }
+
+        let in_check = chess.in_check();
+
+        // a check is searched one ply deeper, so it can't push a threat past the horizon
+        let depth = if in_check { depth + 1 } else { depth };
+
+        if depth <= 0 {
+            return self.quiescence(chess, ply, alpha, beta);
+        }
+
+        let key = chess.hash();
+        let entry = self.table.probe(key);
+        let hash_move = entry.and_then(|entry| entry.value.best_move);
+
+        // the root is always searched, to have a best move
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth as i32 >= depth) {
+            let score = entry.value.score(ply);
+
+            let cutoff = match entry.value.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+
+            if cutoff {
+                self.pv[ply].extend(hash_move);
+                return score;
+            }
+        }
+
+        let previous = self.line.last().copied();
+        // the best line of the previous iteration is only followed while the search is on it
+        let first = self
+            .previous_pv
+            .get(ply)
+            .copied()
+            .filter(|_| self.previous_pv.starts_with(&self.line))
+            .or(hash_move);
+        let moves = MovePicker::new(chess, first, &self.history, ply, previous);
+
+        if !moves.has_legal_moves() {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+
+        let original_alpha = alpha;
+        let mut alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        let mut quiets = vec![];
+
+        for _move in moves {
+            if ply == 0 && self.excluded.contains(&_move) {
+                continue;
+            }
+
+            let score = self.search_move(chess, _move, |search, chess| {
+                -search.negamax(chess, depth - 1, ply + 1, -beta, -alpha)
+            });
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+            }
+
+            if score > alpha {
+                alpha = score;
+                best_move = Some(_move);
+                self.update_pv(ply, _move);
+            }
+
+            if alpha >= beta {
+                if !is_tactical(_move) {
+                    self.history
+                        .record_cutoff(ply, depth, previous, _move, &quiets);
+                }
+
+                break;
+            }
+
+            if !is_tactical(_move) {
+                quiets.push(_move);
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+
+        // without the better lines the root's score isn't the score of the position
+        if ply > 0 || self.excluded.is_empty() {
+            let entry = SearchEntry::new(bound, best_score, ply, best_move.or(hash_move));
+            self.table.store(key, depth as u8, entry);
+        }
+
+        best_score
+    }
+
+    /// Search captures and promotions until the position is quiet. The player to move can
+    /// also stand pat, and keep the static evaluation instead of capturing, except in check
+    /// where every evasion is searched.
+    fn quiescence(&mut self, chess: &mut Chess, ply: usize, alpha: i32, beta: i32) -> i32 {
+        self.pv[ply].clear();
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        self.nodes += 1;
+
+        if ply >= MAX_PLY {
+            return evaluate(chess);
+        }
+
+        let in_check = chess.in_check();
+        let mut alpha = alpha;
+        let mut best_score = -INFINITY;
+
+        if !in_check {
+            best_score = evaluate(chess);
+
+            if best_score >= beta {
+                return best_score;
+            }
+
+            alpha = alpha.max(best_score);
+        }
+
+        let moves = if in_check {
+            let previous = self.line.last().copied();
+            MovePicker::new(chess, None, &self.history, ply, previous)
+        } else {
+            MovePicker::tactical(chess, None)
+        };
+
+        if !moves.has_legal_moves() {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+
+        for _move in moves {
+            let score = self.search_move(chess, _move, |search, chess| {
+                -search.quiescence(chess, ply + 1, -beta, -alpha)
+            });
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+            }
+
+            if score > alpha {
+                alpha = score;
+                self.update_pv(ply, _move);
+            }
+
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best_score
+    }
+
+    /// Play `_move`, search the position with `search`, and take the move back
+    fn search_move(
+        &mut self,
+        chess: &mut Chess,
+        _move: Move,
+        search: impl FnOnce(&mut Search, &mut Chess) -> i32,
+    ) -> i32 {
+        chess.play_move(_move);
+        self.line.push(_move);
+
+        let score = search(self, chess);
+
+        self.line.pop();
+        chess.undo();
+
+        score
+    }
+
+    fn update_pv(&mut self, ply: usize, _move: Move) {
+        let (head, tail) = self.pv.split_at_mut(ply + 1);
+        let pv = &mut head[ply];
+
+        pv.clear();
+        pv.push(_move);
+        pv.extend_from_slice(&tail[0]);
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+
+        if !self.can_stop {
+            return false;
+        }
+
+        if self.stop.load(Ordering::Relaxed) {
+            self.stopped = true;
+            return true;
+        }
+
+        let out_of_nodes = self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes);
+        let out_of_time = self.limits.movetime.is_some_and(|movetime| {
+            self.nodes.is_multiple_of(CLOCK_INTERVAL) && self.clock.elapsed() >= movetime
+        });
+
+        self.stopped = out_of_nodes || out_of_time;
+        self.stopped
+    }
+}
+
+/// Repetitions are scored as draws as soon as the position occurs a second time, playing
+/// for a threefold repetition can't be better than that
+fn is_draw(chess: &Chess) -> bool {
+    chess.half_moves() >= 100 || chess.repetition_count() >= 2 || chess.is_insufficient_materials()
+}
@@ -0,0 +1,244 @@
+//! A hash table of positions already searched, keyed by their Zobrist key.
+//!
+//! The table has a power-of-two number of buckets, each holding a few entries, so a key only
+//! has to be looked for in one bucket. When a bucket is full the entry left by the oldest
+//! search, or else the shallowest, is replaced, since it saved the least work.
+//!
+//! The search stores its scores, bounds and best moves. Perft stores the number of leaf nodes
+//! below each position, so transpositions are only counted once.
+
+use crate::chess::{Chess, Move};
+use crate::search::MATE_BOUND;
+use std::mem::size_of;
+
+/// Entries per bucket
+const BUCKET_SIZE: usize = 4;
+
+/// The size of the table when none is given
+pub const DEFAULT_SIZE_MB: usize = 16;
+
+/// How the stored score relates to the real score of the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// the score is exact
+    Exact,
+    /// a move reached beta, the real score can be higher
+    Lower,
+    /// no move reached alpha, the real score can be lower
+    Upper,
+}
+
+/// What the search found out about a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchEntry {
+    pub bound: Bound,
+    /// mates are counted from this position, not from the root
+    score: i32,
+    pub best_move: Option<Move>,
+}
+
+impl SearchEntry {
+    /// `score` is relative to the root, `ply` plies above this position
+    pub fn new(bound: Bound, score: i32, ply: usize, best_move: Option<Move>) -> SearchEntry {
+        let score = if score > MATE_BOUND {
+            score + ply as i32
+        } else if score < -MATE_BOUND {
+            score - ply as i32
+        } else {
+            score
+        };
+
+        SearchEntry {
+            bound,
+            score,
+            best_move,
+        }
+    }
+
+    /// The score relative to a root `ply` plies above this position. The same position can be
+    /// reached at another ply than the one it was stored from, and its mates are that much
+    /// nearer or further from the root.
+    pub fn score(&self, ply: usize) -> i32 {
+        if self.score > MATE_BOUND {
+            self.score - ply as i32
+        } else if self.score < -MATE_BOUND {
+            self.score + ply as i32
+        } else {
+            self.score
+        }
+    }
+}
+
+/// A stored value and the depth it was searched to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry<T> {
+    pub depth: u8,
+    pub value: T,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot<T> {
+    key: u64,
+    depth: u8,
+    /// the search it was stored by
+    generation: u8,
+    value: T,
+}
+
+type Bucket<T> = [Option<Slot<T>>; BUCKET_SIZE];
+
+pub struct TranspositionTable<T = SearchEntry> {
+    buckets: Vec<Bucket<T>>,
+    generation: u8,
+}
+
+impl<T: Copy> TranspositionTable<T> {
+    /// A table that takes at most `mb` megabytes, and at least one bucket
+    pub fn new(mb: usize) -> TranspositionTable<T> {
+        let mut table = TranspositionTable {
+            buckets: vec![],
+            generation: 0,
+        };
+
+        table.resize(mb);
+        table
+    }
+
+    /// Resize the table to at most `mb` megabytes, which clears it
+    pub fn resize(&mut self, mb: usize) {
+        let buckets = (mb * 1024 * 1024 / size_of::<Bucket<T>>()).max(1);
+
+        // the largest power of two that fits, so the bucket is a mask of the key
+        let buckets = 1 << (usize::BITS - 1 - buckets.leading_zeros());
+
+        self.buckets = vec![[None; BUCKET_SIZE]; buckets];
+        self.generation = 0;
+    }
+
+    pub fn clear(&mut self) {
+        for bucket in self.buckets.iter_mut() {
+            *bucket = [None; BUCKET_SIZE];
+        }
+
+        self.generation = 0;
+    }
+
+    /// The number of entries the table can hold
+    pub fn capacity(&self) -> usize {
+        self.buckets.len() * BUCKET_SIZE
+    }
+
+    /// Start a new search. The entries of the previous searches are kept, but are replaced
+    /// before the entries of this one.
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry<T>> {
+        self.bucket(key)
+            .iter()
+            .flatten()
+            .find(|slot| slot.key == key)
+            .map(|slot| Entry {
+                depth: slot.depth,
+                value: slot.value,
+            })
+    }
+
+    /// Store the value of a position, replacing what was stored for it before
+    pub fn store(&mut self, key: u64, depth: u8, value: T) {
+        let generation = self.generation;
+        let bucket = self.bucket_mut(key);
+
+        let index = bucket
+            .iter()
+            .position(|slot| slot.is_none_or(|slot| slot.key == key))
+            .unwrap_or_else(|| {
+                // the slots are all taken by other positions
+                let worth = |slot: &Option<Slot<T>>| {
+                    slot.map_or(0, |slot| {
+                        let age = generation.wrapping_sub(slot.generation) as i32;
+
+                        slot.depth as i32 - 8 * age
+                    })
+                };
+
+                (0..BUCKET_SIZE)
+                    .min_by_key(|&index| worth(&bucket[index]))
+                    .expect("a bucket has slots")
+            });
+
+        bucket[index] = Some(Slot {
+            key,
+            depth,
+            generation,
+            value,
+        });
+    }
+
+    /// How full the table is with entries of the current search, in permille, by sampling the
+    /// first thousand entries
+    pub fn hashfull(&self) -> u16 {
+        let sample = 1000.min(self.capacity());
+
+        let used = self
+            .buckets
+            .iter()
+            .flatten()
+            .take(sample)
+            .flatten()
+            .filter(|slot| slot.generation == self.generation)
+            .count();
+
+        (used * 1000 / sample) as u16
+    }
+
+    fn bucket(&self, key: u64) -> &Bucket<T> {
+        &self.buckets[key as usize & (self.buckets.len() - 1)]
+    }
+
+    fn bucket_mut(&mut self, key: u64) -> &mut Bucket<T> {
+        let mask = self.buckets.len() - 1;
+
+        &mut self.buckets[key as usize & mask]
+    }
+}
+
+impl<T: Copy> Default for TranspositionTable<T> {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_SIZE_MB)
+    }
+}
+
+/// Count the leaf nodes `depth` plies below the position of `chess`, like `Chess::perft`,
+/// caching the count of each position in `table`
+pub fn perft(chess: &mut Chess, depth: u8, table: &mut TranspositionTable<u64>) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    // the last ply is cheaper to count than to look up
+    if depth == 1 {
+        return chess.generate_legal_moves().len() as u64;
+    }
+
+    let key = chess.hash();
+
+    if let Some(entry) = table.probe(key) {
+        if entry.depth == depth {
+            return entry.value;
+        }
+    }
+
+    let mut nodes = 0;
+
+    for _move in chess.generate_legal_moves() {
+        chess.play_move(_move);
+        nodes += perft(chess, depth - 1, table);
+        chess.undo();
+    }
+
+    table.store(key, depth, nodes);
+
+    nodes
+}
@@ -0,0 +1,379 @@
+//! The JavaScript API. `Game` wraps a `Chess` and converts its moves and outcomes to plain
+//! objects with string fields, errors are thrown as JavaScript `Error`s named after the Rust
+//! error type.
+//!
+//! The TypeScript declarations are written by hand in `TYPESCRIPT`, so the string fields can
+//! be typed as the exact values they hold. Keep them in sync with the methods below.
+
+use crate::chess::{Chess, Move};
+use crate::chess2::Square;
+use crate::errors::{FenError, MoveError};
+use crate::eval::{self, Term};
+use crate::outcome::{Outcome, Termination};
+use crate::pgn::DEFAULT_POSITION;
+use crate::search::{Search, SearchLimits};
+use crate::utils::set_panic_hook;
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT: &str = r#"
+export type Color = "w" | "b";
+
+export type PieceSymbol = "p" | "n" | "b" | "r" | "q" | "k";
+
+export type Square = `${"a" | "b" | "c" | "d" | "e" | "f" | "g" | "h"}${1 | 2 | 3 | 4 | 5 | 6 | 7 | 8}`;
+
+export type Termination =
+  | "checkmate"
+  | "stalemate"
+  | "insufficient-material"
+  | "fifty-moves"
+  | "seventy-five-moves"
+  | "threefold-repetition"
+  | "fivefold-repetition"
+  | "resignation"
+  | "timeout"
+  | "agreement";
+
+export interface Move {
+  free(): void;
+  readonly color: Color;
+  readonly piece: PieceSymbol;
+  readonly from: Square;
+  readonly to: Square;
+  readonly san: string;
+  readonly uci: string;
+  readonly captured?: PieceSymbol;
+  readonly promotion?: PieceSymbol;
+}
+
+export interface Outcome {
+  free(): void;
+  /** undefined for a draw */
+  readonly winner?: Color;
+  readonly termination: Termination;
+}
+
+export type EvaluationTermName =
+  | "material"
+  | "piece-squares"
+  | "pawn-structure"
+  | "mobility"
+  | "king-safety"
+  | "bishop-pair";
+
+export interface EvaluationTerm {
+  free(): void;
+  readonly term: EvaluationTermName;
+  /** what the term is worth to white, in centipawns */
+  readonly white: number;
+  readonly black: number;
+}
+
+export interface Evaluation {
+  free(): void;
+  /** in centipawns, from the point of view of the player to move */
+  readonly score: number;
+  /** from 0 for a pawn endgame to 24 with all the pieces on the board */
+  readonly phase: number;
+  readonly terms: EvaluationTerm[];
+}
+
+/** Every method of `Game` throws these instead of returning errors */
+export interface GameError extends Error {
+  name: "MoveError" | "FenError";
+}
+
+export class Game {
+  free(): void;
+  /** Start from `fen`, or from the standard position if it isn't given */
+  constructor(fen?: string);
+  loadFen(fen: string): void;
+  fen(): string;
+  /** The legal moves of the player to move, only those of the piece on `square` if given */
+  legalMoves(square?: Square): Move[];
+  /** Play a move written in SAN (`Nf3`) or UCI notation (`g1f3`) */
+  move(notation: string): Move;
+  /** Take back the last move, undefined if no move was played */
+  undo(): Move | undefined;
+  turn(): Color;
+  inCheck(): boolean;
+  /** undefined while the game is still going on */
+  outcome(): Outcome | undefined;
+  /**
+   * The move the engine would play after searching `depth` plies or for `movetime`
+   * milliseconds, whichever comes first, 4 plies if neither is given. undefined if there is
+   * no legal move.
+   */
+  bestMove(depth?: number, movetime?: number): Move | undefined;
+  /** The static evaluation of the position, term by term */
+  evaluate(): Evaluation;
+  history(): Move[];
+}
+"#;
+
+/// A move with the squares, pieces and notations a UI needs
+#[wasm_bindgen(js_name = Move, getter_with_clone, skip_typescript)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameMove {
+    /// `w` or `b`
+    pub color: String,
+    /// `p`, `n`, `b`, `r`, `q` or `k`
+    pub piece: String,
+    pub from: String,
+    pub to: String,
+    pub san: String,
+    pub uci: String,
+    pub captured: Option<String>,
+    pub promotion: Option<String>,
+}
+
+impl GameMove {
+    /// `chess` must be the position the move is played from, to write its SAN
+    fn new(chess: &mut Chess, _move: Move) -> Self {
+        let piece = _move.moved_piece();
+
+        Self {
+            color: piece.color.to_string(),
+            piece: piece.role.to_string(),
+            from: _move.from_square().to_string(),
+            to: _move.to_square().to_string(),
+            san: chess.san(_move),
+            uci: _move.to_uci(),
+            captured: _move.captured_piece().map(|piece| piece.role.to_string()),
+            promotion: _move.promotion_role().map(|role| role.to_string()),
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = Outcome, getter_with_clone, skip_typescript)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameOutcome {
+    /// `w` or `b`, or `undefined` for a draw
+    pub winner: Option<String>,
+    /// `checkmate`, `stalemate`, `insufficient-material`, `fifty-moves`, `seventy-five-moves`,
+    /// `threefold-repetition`, `fivefold-repetition`, `resignation`, `timeout` or `agreement`
+    pub termination: String,
+}
+
+impl From<Outcome> for GameOutcome {
+    fn from(outcome: Outcome) -> Self {
+        let termination = match outcome.termination {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::InsufficientMaterial => "insufficient-material",
+            Termination::FiftyMoves => "fifty-moves",
+            Termination::SeventyFiveMoves => "seventy-five-moves",
+            Termination::ThreefoldRepetition => "threefold-repetition",
+            Termination::FivefoldRepetition => "fivefold-repetition",
+            Termination::Resignation => "resignation",
+            Termination::Timeout => "timeout",
+            Termination::Agreement => "agreement",
+        };
+
+        Self {
+            winner: outcome.winner.map(|color| color.to_string()),
+            termination: termination.to_string(),
+        }
+    }
+}
+
+/// The static evaluation of a position and the terms it is made of
+#[wasm_bindgen(js_name = Evaluation, getter_with_clone, skip_typescript)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameEvaluation {
+    /// from the point of view of the player to move
+    pub score: i32,
+    pub phase: i32,
+    pub terms: Vec<EvaluationTerm>,
+}
+
+#[wasm_bindgen(getter_with_clone, skip_typescript)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvaluationTerm {
+    /// `material`, `piece-squares`, `pawn-structure`, `mobility`, `king-safety` or
+    /// `bishop-pair`
+    pub term: String,
+    pub white: i32,
+    pub black: i32,
+}
+
+impl From<eval::Breakdown> for GameEvaluation {
+    fn from(breakdown: eval::Breakdown) -> Self {
+        let terms = Term::ALL
+            .iter()
+            .map(|&term| {
+                let score = breakdown.term(term);
+
+                EvaluationTerm {
+                    term: term.to_string(),
+                    white: score.white.taper(breakdown.phase),
+                    black: score.black.taper(breakdown.phase),
+                }
+            })
+            .collect();
+
+        Self {
+            score: breakdown.total(),
+            phase: breakdown.phase,
+            terms,
+        }
+    }
+}
+
+/// A game that can be played from JavaScript
+#[wasm_bindgen(skip_typescript)]
+pub struct Game {
+    chess: Chess,
+    /// kept between `bestMove` calls, so the transposition table is only allocated once
+    search: Search,
+}
+
+#[wasm_bindgen]
+impl Game {
+    /// Start from `fen`, or from the standard position if it isn't given
+    #[wasm_bindgen(constructor)]
+    pub fn new(fen: Option<String>) -> Result<Game, JsValue> {
+        set_panic_hook();
+
+        let mut game = Game {
+            chess: Chess::new(),
+            search: Search::new(SearchLimits::default()),
+        };
+
+        game.load_fen(fen.as_deref().unwrap_or(DEFAULT_POSITION))?;
+
+        Ok(game)
+    }
+
+    /// Replace the game with the position in `fen`, throws if the FEN is invalid
+    #[wasm_bindgen(js_name = loadFen)]
+    pub fn load_fen(&mut self, fen: &str) -> Result<(), JsValue> {
+        self.chess.try_load_fen(fen)?;
+        self.search.table_mut().clear();
+
+        Ok(())
+    }
+
+    pub fn fen(&self) -> String {
+        self.chess.get_fen()
+    }
+
+    /// The legal moves of the player to move, only those of the piece on `square` if given
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&mut self, square: Option<String>) -> Result<Vec<GameMove>, JsValue> {
+        let from = match square {
+            Some(square) => Some(
+                square
+                    .parse::<Square>()
+                    .map_err(|_| MoveError::InvalidSquare(square))?,
+            ),
+            None => None,
+        };
+
+        let moves = self.chess.generate_legal_moves();
+
+        Ok(moves
+            .into_iter()
+            .filter(|m| from.is_none_or(|from| m.from_square() == from))
+            .map(|m| GameMove::new(&mut self.chess, m))
+            .collect())
+    }
+
+    /// Play a move written in SAN (`Nf3`) or UCI notation (`g1f3`) and return it, throws if
+    /// the move is illegal
+    #[wasm_bindgen(js_name = move)]
+    pub fn play(&mut self, notation: &str) -> Result<GameMove, JsValue> {
+        let _move = match self.chess.parse_uci(notation) {
+            Err(MoveError::InvalidNotation(_)) => self.chess.parse_san(notation)?,
+            result => result?,
+        };
+
+        let game_move = GameMove::new(&mut self.chess, _move);
+        self.chess.play_move(_move);
+
+        Ok(game_move)
+    }
+
+    /// Take back the last move and return it, or `undefined` if no move was played
+    pub fn undo(&mut self) -> Option<GameMove> {
+        let _move = self.chess.history().pop()?;
+        self.chess.undo();
+
+        Some(GameMove::new(&mut self.chess, _move))
+    }
+
+    /// `w` or `b`
+    pub fn turn(&self) -> String {
+        self.chess.turn().to_string()
+    }
+
+    #[wasm_bindgen(js_name = inCheck)]
+    pub fn in_check(&self) -> bool {
+        self.chess.in_check()
+    }
+
+    /// How the game ended, or `undefined` while it is still going on
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        self.chess.outcome().map(GameOutcome::from)
+    }
+
+    /// The move the engine would play after searching `depth` plies or for `movetime`
+    /// milliseconds, whichever comes first, 4 plies if neither is given
+    #[wasm_bindgen(js_name = bestMove)]
+    pub fn best_move(&mut self, depth: Option<u8>, movetime: Option<u32>) -> Option<GameMove> {
+        let limits = SearchLimits {
+            depth: depth.or(if movetime.is_none() { Some(4) } else { None }),
+            nodes: None,
+            movetime: movetime.map(|millis| Duration::from_millis(millis.into())),
+        };
+
+        self.search.set_limits(limits);
+        let _move = self.search.run(&mut self.chess).best_move?;
+
+        Some(GameMove::new(&mut self.chess, _move))
+    }
+
+    /// The static evaluation of the position, term by term
+    pub fn evaluate(&self) -> GameEvaluation {
+        eval::breakdown(&self.chess).into()
+    }
+
+    /// The moves played since the position was loaded
+    pub fn history(&self) -> Vec<GameMove> {
+        let mut chess = Chess::new();
+        chess.set_chess960(self.chess.is_chess960());
+        chess.load_fen(self.chess.starting_fen().to_string());
+
+        self.chess
+            .history()
+            .into_iter()
+            .map(|_move| {
+                let game_move = GameMove::new(&mut chess, _move);
+                chess.play_move(_move);
+
+                game_move
+            })
+            .collect()
+    }
+}
+
+impl From<MoveError> for JsValue {
+    fn from(error: MoveError) -> Self {
+        js_error("MoveError", error.to_string())
+    }
+}
+
+impl From<FenError> for JsValue {
+    fn from(error: FenError) -> Self {
+        js_error("FenError", error.to_string())
+    }
+}
+
+fn js_error(name: &str, message: String) -> JsValue {
+    let error = js_sys::Error::new(&message);
+    error.set_name(name);
+
+    error.into()
+}
@@ -0,0 +1,161 @@
+use chess_wasm::chess::*;
+use chess_wasm::chess2::{self, *};
+
+const POSITIONS: [&str; 6] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+];
+
+// https://www.chessprogramming.org/Perft_Results
+#[test]
+fn perft_reference_positions() {
+    let expected: [&[u64]; 6] = [
+        &[20, 400, 8902, 197281],
+        &[48, 2039, 97862],
+        &[14, 191, 2812, 43238],
+        &[6, 264, 9467],
+        &[44, 1486, 62379],
+        &[46, 2079, 89890],
+    ];
+
+    for (fen, counts) in POSITIONS.iter().zip(expected) {
+        let position = Position::from_fen(fen).unwrap();
+
+        for (depth, &count) in counts.iter().enumerate() {
+            assert_eq!(position.perft(depth as u8 + 1), count, "{} {}", fen, depth);
+        }
+    }
+}
+
+/// The moves of every piece, as returned by `Chess::moves`
+fn moves_by_square(chess: &mut Chess) -> Vec<String> {
+    let mut moves = vec![];
+
+    for square in Square::all() {
+        moves.extend(chess.moves(square));
+    }
+
+    moves.sort();
+    moves
+}
+
+/// Legal moves found the slow way, by playing every pseudo-legal move
+fn filtered_pseudo_legal_moves(position: &Position) -> Vec<Move> {
+    let us = position.turn();
+
+    position
+        .pseudo_legal_moves()
+        .into_iter()
+        .filter(|&_move| {
+            let mut next = *position;
+            next.play(_move);
+
+            next.king(us)
+                .is_none_or(|king| !next.is_attacked(king, us.other()))
+        })
+        .collect()
+}
+
+fn compare_square_moves(chess: &mut Chess, depth: u8) {
+    let fen = chess.get_fen();
+    let legal_moves = filtered_pseudo_legal_moves(&Position::from(&*chess));
+
+    let mut sans: Vec<String> = legal_moves.iter().map(|&m| chess.san(m)).collect();
+    sans.sort();
+
+    assert_eq!(moves_by_square(chess), sans, "{}", fen);
+
+    if depth == 0 {
+        return;
+    }
+
+    for _move in legal_moves {
+        chess.make_move(_move).unwrap();
+        compare_square_moves(chess, depth - 1);
+        chess.undo();
+    }
+}
+
+#[test]
+fn square_moves_are_the_legal_moves() {
+    for fen in POSITIONS {
+        let mut chess = Chess::new();
+        chess.load_fen(fen.to_string());
+
+        compare_square_moves(&mut chess, 1);
+    }
+}
+
+#[test]
+fn attacks_and_check() {
+    let position = Position::from_fen("4k3/8/8/8/1b6/8/8/4K3 w - - 0 1").unwrap();
+
+    assert!(position.in_check());
+    assert_eq!(position.turn(), Color::White);
+    assert_eq!(
+        position.piece_at(Square::B4),
+        Some(chess2::Piece::new(Color::Black, Role::Bishop))
+    );
+    assert!(position.is_attacked(Square::C3, Color::Black));
+    assert!(!position.is_attacked(Square::B3, Color::Black));
+    assert_eq!(position.legal_moves().len(), 4);
+}
+
+fn compare_legal_generation(position: &Position, depth: u8) {
+    let mut legal_moves = position.legal_moves();
+    let mut expected = filtered_pseudo_legal_moves(position);
+
+    legal_moves.sort_by_key(Move::to_uci);
+    expected.sort_by_key(Move::to_uci);
+
+    assert_eq!(legal_moves, expected, "{:?}", position);
+
+    for _move in position.pseudo_legal_moves() {
+        let from = _move.from_square();
+        let to = _move.to_square();
+
+        assert_eq!(
+            position.is_legal(from, to),
+            expected.contains(&_move),
+            "{:?} {}",
+            position,
+            _move.to_uci()
+        );
+    }
+
+    if depth > 0 {
+        for _move in legal_moves {
+            let mut next = *position;
+            next.play(_move);
+            compare_legal_generation(&next, depth - 1);
+        }
+    }
+}
+
+#[test]
+fn legal_moves_without_playing_them() {
+    let positions = [
+        // en passant that would expose the king along the rank
+        "8/8/8/K2pP2r/8/8/8/7k w - d6 0 1",
+        // en passant by a pinned pawn, off and along its pin
+        "8/8/5b2/3pP3/8/8/8/K6k w - d6 0 1",
+        "8/8/k7/8/2pP4/8/4B3/4K3 b - d3 0 1",
+        "7k/8/8/8/3pP3/8/8/K1B5 b - e3 0 1",
+        // en passant that captures the checking pawn
+        "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+        // double check
+        "4k3/8/8/8/8/5n2/8/R3K2r w Q - 0 1",
+        // pinned pieces
+        "4k3/4r3/8/b7/8/2N5/3P4/4K3 w - - 0 1",
+    ];
+
+    for fen in positions.iter().chain(POSITIONS.iter()) {
+        let position = Position::from_fen(fen).unwrap();
+
+        compare_legal_generation(&position, 2);
+    }
+}
@@ -1,45 +1,57 @@
-use chess_wasm::chess::*;
+use chess_wasm::chess::Chess;
+use chess_wasm::chess2::{Color, Piece, Role, Square};
 
 #[test]
-fn default_board_test() {
+fn pieces_snapshot() {
     let mut chess = Chess::new();
 
     chess.load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
 
-    #[rustfmt::skip]
-    assert_eq!(
-        [
-            130, 132, 136, 160, 144, 136, 132, 130, 0, 0, 0, 0, 0, 0, 0, 0, 
-            129, 129, 129, 129, 129, 129, 129, 129, 0, 0, 0, 0, 0, 0, 0, 0, 
-              0,   0,   0,   0,   0,   0,   0,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
-              0,   0,   0,   0,   0,   0,   0,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
-              0,   0,   0,   0,   0,   0,   0,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
-              0,   0,   0,   0,   0,   0,   0,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
-              1,   1,   1,   1,   1,   1,   1,   1, 0, 0, 0, 0, 0, 0, 0, 0, 
-              2,   4,   8,  32,  16,   8,   4,   2, 0, 0, 0, 0, 0, 0, 0, 0
-        ],
-        chess.board
-    );
+    let pieces = chess.pieces();
+    let ranks = [
+        "RNBQKBNR", "PPPPPPPP", "", "", "", "", "pppppppp", "rnbqkbnr",
+    ];
+
+    for square in Square::all() {
+        let expected = ranks[square.rank() as usize]
+            .chars()
+            .nth(square.file() as usize)
+            .and_then(Piece::from_char);
+
+        assert_eq!(pieces[square.index()], expected, "{}", square);
+    }
 }
 
 #[test]
-fn checkmate_board_test() {
+fn piece_at_hides_internal_flags() {
     let mut chess = Chess::new();
 
-    chess.load_fen("r3k2r/ppp2p1p/2n1p1p1/8/2B2P1q/2NPb1n1/PP4PP/R2Q3K w kq - 0 8".to_string());
+    chess.load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
+    chess.move_piece("e4").unwrap();
+    chess.move_piece("Nf6").unwrap();
+    chess.move_piece("e5").unwrap();
+    chess.move_piece("d5").unwrap();
 
-    #[rustfmt::skip]
+    // the pawns have moved and d6 is the en passant square
     assert_eq!(
-        [
-            130,   0,   0,   0, 144,   0,   0, 130, 0, 0, 0, 0, 0, 0, 0, 0, 
-            129, 129, 129,   0,   0, 129,   0, 129, 0, 0, 0, 0, 0, 0, 0, 0, 
-              0,   0, 132,   0, 193,   0, 193,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
-              0,   0,   0,   0,   0,   0,   0,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
-              0,   0,   8,   0,   0,  65,   0, 160, 0, 0, 0, 0, 0, 0, 0, 0, 
-              0,   0,   4,  65, 136,   0, 132,   0, 0, 0, 0, 0, 0, 0, 0, 0, 
-              1,   1,   0,   0,   0,   0,   1,   1, 0, 0, 0, 0, 0, 0, 0, 0, 
-              2,   0,   0,  32,   0,   0,   0,  16, 0, 0, 0, 0, 0, 0, 0, 0
-        ],
-        chess.board
+        chess.piece_at(Square::E5),
+        Some(Piece::new(Color::White, Role::Pawn))
     );
+    assert_eq!(
+        chess.piece_at(Square::D5),
+        Some(Piece::new(Color::Black, Role::Pawn))
+    );
+    assert_eq!(chess.piece_at(Square::D6), None);
+    assert_eq!(chess.piece_at(Square::E2), None);
+    assert_eq!(
+        chess.piece_at(Square::F6),
+        Some(Piece::new(Color::Black, Role::Knight))
+    );
+
+    let count = chess
+        .pieces()
+        .iter()
+        .filter(|piece| piece.is_some())
+        .count();
+    assert_eq!(count, 32);
 }
@@ -0,0 +1,234 @@
+use chess_wasm::chess::*;
+use chess_wasm::chess2::{Position, Role};
+use chess_wasm::chess960;
+use chess_wasm::errors::*;
+use chess_wasm::pgn::*;
+
+fn chess960(fen: &str) -> Chess {
+    let mut chess = Chess::new();
+    chess.set_chess960(true);
+    chess.load_fen(fen.to_string());
+
+    chess
+}
+
+// https://www.chessprogramming.org/Chess960_Perft_Results
+#[test]
+fn perft() {
+    let positions = [
+        (
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+            [21, 528, 12189, 326672],
+        ),
+        (
+            "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+            [21, 807, 18002, 667366],
+        ),
+        (
+            "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+            [20, 479, 10471, 273318],
+        ),
+        (
+            "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9",
+            [22, 593, 13440, 382958],
+        ),
+    ];
+
+    for (fen, nodes) in positions {
+        let mut chess = Chess::new();
+        chess.load_fen(fen.to_string());
+
+        assert!(chess.is_chess960(), "{}", fen);
+
+        let position = Position::from(&chess);
+
+        for (depth, &expected) in (1..).zip(nodes.iter()) {
+            assert_eq!(position.perft(depth), expected, "{} depth {}", fen, depth);
+        }
+
+        // playing and undoing the moves on the 0x88 board
+        assert_eq!(chess.perft(3), nodes[2], "{}", fen);
+    }
+}
+
+#[test]
+fn starting_positions() {
+    assert_eq!(
+        chess960::starting_fen(518).as_deref(),
+        Some("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+    );
+    assert_eq!(
+        chess960::starting_fen(0).as_deref(),
+        Some("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1")
+    );
+    assert_eq!(
+        chess960::starting_fen(959).as_deref(),
+        Some("rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1")
+    );
+    assert_eq!(chess960::starting_fen(960), None);
+    assert!(Chess::new_chess960(960).is_none());
+
+    let mut back_ranks = std::collections::HashSet::new();
+
+    for index in 0..960 {
+        let rank = chess960::back_rank(index).unwrap();
+        let files = |role| (0..8).filter(move |&file| rank[file] == role);
+
+        let bishops: Vec<usize> = files(Role::Bishop).collect();
+        let rooks: Vec<usize> = files(Role::Rook).collect();
+        let king = files(Role::King).next().unwrap();
+
+        assert_ne!(bishops[0] % 2, bishops[1] % 2, "{}", index);
+        assert!(rooks[0] < king && king < rooks[1], "{}", index);
+        assert!(back_ranks.insert(rank), "{}", index);
+
+        let chess = Chess::new_chess960(index).unwrap();
+        assert_eq!(chess.castling_rights(), [true, true, true, true]);
+    }
+}
+
+#[test]
+fn castling_is_written_as_the_king_taking_its_rook() {
+    let fen = "rk5r/pppppppp/8/8/8/8/PPPPPPPP/RK5R w KQkq - 0 1";
+    let mut chess = chess960(fen);
+
+    let king_side = chess.parse_san("O-O").unwrap();
+    assert_eq!(king_side.to_uci(), "b1h1");
+    assert_eq!(king_side.flags(), MoveFlag::KING_SIDE_CASTLE);
+    assert_eq!(king_side.captured_piece(), None);
+    assert_eq!(chess.san(king_side), "O-O");
+
+    // the king moves to the right when castling queen side from the b-file
+    let queen_side = chess.make_uci_move("b1a1").unwrap();
+    assert_eq!(queen_side.flags(), MoveFlag::QUEEN_SIDE_CASTLE);
+    assert_eq!(
+        chess.get_fen(),
+        "rk5r/pppppppp/8/8/8/8/PPPPPPPP/2KR3R b kq - 1 1"
+    );
+
+    chess.make_uci_move("b8h8").unwrap();
+    assert_eq!(
+        chess.get_fen(),
+        "r4rk1/pppppppp/8/8/8/8/PPPPPPPP/2KR3R w - - 2 2"
+    );
+    assert_eq!(chess.history()[1].to_uci(), "b8h8");
+
+    chess.undo();
+    chess.undo();
+    assert_eq!(chess.get_fen(), fen);
+}
+
+#[test]
+fn castling_when_the_king_or_rook_stays() {
+    // the king is already on g1, and the rook is on the king's destination
+    let fen = "4k3/8/8/8/8/8/8/6KR w K - 0 1";
+    let mut chess = chess960(fen);
+
+    chess.move_piece("O-O").unwrap();
+    assert_eq!(chess.get_fen(), "4k3/8/8/8/8/8/8/5RK1 b - - 1 1");
+    chess.undo();
+
+    let fen = "4k3/8/8/8/8/8/8/2R1K3 w Q - 0 1";
+    let mut chess = chess960(fen);
+
+    chess.move_piece("O-O-O").unwrap();
+    assert_eq!(chess.get_fen(), "4k3/8/8/8/8/8/8/2KR4 b - - 1 1");
+    chess.undo();
+    assert_eq!(chess.get_fen(), fen);
+}
+
+#[test]
+fn castling_path() {
+    // the squares the king and rook go through must be empty
+    let mut chess = chess960("4k3/8/8/8/8/8/8/RN2K3 w Q - 0 1");
+    assert_eq!(
+        chess.move_piece("O-O-O"),
+        Err(MoveError::IllegalQueenSideCastle)
+    );
+
+    // the king can't go through an attacked square
+    let mut chess = chess960("4k3/8/8/8/8/8/5r2/1K5R w K - 0 1");
+    assert_eq!(
+        chess.move_piece("O-O"),
+        Err(MoveError::IllegalKingSideCastle)
+    );
+
+    // the rook on b1 shields c1 from the rook on a1 until it moves to d1
+    let mut chess = chess960("4k3/8/8/8/8/8/8/rR1K4 w Q - 0 1");
+    assert_eq!(
+        chess.move_piece("O-O-O"),
+        Err(MoveError::IllegalQueenSideCastle)
+    );
+    assert!(chess.make_uci_move("d1b1").is_err());
+}
+
+#[test]
+fn shredder_and_x_fen() {
+    // the files of the rooks turn on Chess960
+    let mut chess = Chess::new();
+    chess.load_fen("4k3/8/8/8/8/8/8/1R2K1RR w G - 0 1".to_string());
+
+    assert!(chess.is_chess960());
+    assert_eq!(
+        chess.make_uci_move("e1g1").unwrap().flags(),
+        MoveFlag::KING_SIDE_CASTLE
+    );
+    chess.undo();
+
+    // K means the outermost rook, so the inner one is written with its file
+    assert_eq!(chess.get_fen(), "4k3/8/8/8/8/8/8/1R2K1RR w G - 0 1");
+
+    let mut chess = chess960("4k3/8/8/8/8/8/8/1R2K1RR w KQ - 0 1");
+    assert_eq!(chess.get_fen(), "4k3/8/8/8/8/8/8/1R2K1RR w KQ - 0 1");
+    assert!(chess.parse_uci("e1b1").is_ok());
+
+    // the rook on g1 is in the way of the king
+    assert_eq!(chess.parse_uci("e1h1"), Err(MoveError::IllegalMove));
+
+    chess.load_fen("4k3/8/8/8/8/8/8/1R2K1RR w HB - 0 1".to_string());
+    assert_eq!(chess.get_fen(), "4k3/8/8/8/8/8/8/1R2K1RR w KQ - 0 1");
+
+    // outside of Chess960 the rooks must be in the corners and the king on the e-file
+    let mut chess = Chess::new();
+    chess.load_fen("4k3/8/8/8/8/8/8/1R3K1R w KQ - 0 1".to_string());
+    assert_eq!(chess.castling_rights(), [false, false, false, false]);
+
+    assert_eq!(
+        chess.try_load_fen("4k3/8/8/8/8/8/8/1R2K1RR w GH - 0 1"),
+        Err(FenError::InvalidCastlingRights("GH".to_string()))
+    );
+    assert_eq!(
+        chess.try_load_fen("4k3/8/8/8/8/8/8/1R2K1RR w KG - 0 1"),
+        Err(FenError::InvalidCastlingRights("KG".to_string()))
+    );
+}
+
+#[test]
+fn standard_castling_is_unchanged() {
+    let mut chess = Chess::new();
+    chess.load_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".to_string());
+
+    assert!(!chess.is_chess960());
+    assert_eq!(chess.parse_san("O-O").unwrap().to_uci(), "e1g1");
+    assert!(chess.parse_uci("e1h1").is_err());
+}
+
+#[test]
+fn pgn_variant_tag() {
+    let mut chess = Chess::new_chess960(0).unwrap();
+
+    // the rights are lost when loading the X-FEN of this position as standard chess
+    for san in ["g3", "g6", "Ng2", "Ng7"] {
+        chess.move_piece(san).unwrap();
+    }
+
+    let pgn = chess.pgn(&[]);
+    assert!(pgn.contains("[Variant \"Chess960\"]"), "{}", pgn);
+
+    let game: Game = pgn.parse().unwrap();
+    let mut replayed = Chess::new();
+    game.replay(&mut replayed).unwrap();
+
+    assert!(replayed.is_chess960());
+    assert_eq!(replayed.get_fen(), chess.get_fen());
+}
@@ -0,0 +1,188 @@
+use chess_wasm::chess2::Color;
+use chess_wasm::eval::*;
+use chess_wasm::pgn::DEFAULT_POSITION;
+
+mod utils;
+use utils::chess;
+
+/// The same position with the colors swapped and the board flipped
+fn mirror(fen: &str) -> String {
+    let parts: Vec<&str> = fen.split(' ').collect();
+
+    let board: Vec<String> = parts[0]
+        .split('/')
+        .rev()
+        .map(|rank| rank.chars().map(swap_case).collect())
+        .collect();
+
+    let turn = if parts[1] == "w" { "b" } else { "w" };
+    let castling: String = parts[2].chars().map(swap_case).collect();
+
+    format!("{} {} {} - 0 1", board.join("/"), turn, castling)
+}
+
+fn swap_case(c: char) -> char {
+    if c.is_ascii_uppercase() {
+        c.to_ascii_lowercase()
+    } else {
+        c.to_ascii_uppercase()
+    }
+}
+
+#[test]
+fn starting_position_is_balanced() {
+    let breakdown = breakdown(&chess(DEFAULT_POSITION));
+
+    assert_eq!(breakdown.phase, MAX_PHASE);
+    assert_eq!(breakdown.total(), 0);
+
+    for term in breakdown.terms.iter() {
+        assert_eq!(term.white, term.black, "{}", term.term);
+    }
+}
+
+#[test]
+fn symmetry() {
+    let fens = [
+        "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "4k3/8/8/3PP3/8/8/8/4K3 b - - 0 1",
+    ];
+
+    for fen in fens.iter() {
+        let mirrored = mirror(fen);
+
+        assert_eq!(
+            evaluate(&chess(fen)),
+            evaluate(&chess(&mirrored)),
+            "{} {}",
+            fen,
+            mirrored
+        );
+    }
+}
+
+#[test]
+fn side_to_move() {
+    let white = evaluate(&chess("4k3/8/8/8/8/8/8/3QK3 w - - 0 1"));
+    let black = evaluate(&chess("4k3/8/8/8/8/8/8/3QK3 b - - 0 1"));
+
+    assert!(white > 800, "{}", white);
+    assert_eq!(white, -black);
+}
+
+#[test]
+fn terms_add_up() {
+    let breakdown = breakdown(&chess(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+    ));
+
+    let white: i32 = Term::ALL
+        .iter()
+        .map(|&term| breakdown.value(term, Color::White))
+        .sum();
+
+    assert_eq!(breakdown.turn, Color::Black);
+    assert_eq!(breakdown.total(), -white);
+    assert_eq!(breakdown.term(Term::Mobility).term, Term::Mobility);
+}
+
+#[test]
+fn tapering() {
+    let score = Score::new(100, 200);
+
+    assert_eq!(score.taper(MAX_PHASE), 100);
+    assert_eq!(score.taper(0), 200);
+    assert_eq!(score.taper(MAX_PHASE / 2), 150);
+
+    // two rooks and a knight on each side
+    let breakdown = breakdown(&chess("rn2k2r/8/8/8/8/8/8/RN2K2R w - - 0 1"));
+    assert_eq!(breakdown.phase, 10);
+}
+
+#[test]
+fn pawn_structure() {
+    // doubled, isolated and passed
+    let pawns = breakdown(&chess("4k3/8/8/8/8/P7/P7/4K3 w - - 0 1")).term(Term::PawnStructure);
+
+    assert_eq!(
+        pawns.white,
+        WEIGHTS.doubled_pawn
+            + WEIGHTS.isolated_pawn * 2
+            + WEIGHTS.passed_pawn[1]
+            + WEIGHTS.passed_pawn[2]
+    );
+    assert_eq!(pawns.black, Score::ZERO);
+
+    // the pawns stop each other from being passed
+    let pawns = breakdown(&chess("4k3/1p6/8/8/8/8/P7/4K3 w - - 0 1")).term(Term::PawnStructure);
+
+    assert_eq!(pawns.white, WEIGHTS.isolated_pawn);
+    assert_eq!(pawns.black, WEIGHTS.isolated_pawn);
+
+    // connected pawns, and an isolated pawn on black's 5th rank
+    let pawns = breakdown(&chess("4k3/8/8/8/4p3/8/PP6/4K3 w - - 0 1")).term(Term::PawnStructure);
+
+    assert_eq!(pawns.white, WEIGHTS.passed_pawn[1] * 2);
+    assert_eq!(pawns.black, WEIGHTS.isolated_pawn + WEIGHTS.passed_pawn[4]);
+}
+
+#[test]
+fn mobility() {
+    let centre = breakdown(&chess("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1"));
+    let corner = breakdown(&chess("4k3/8/8/8/8/8/8/N3K3 w - - 0 1"));
+
+    assert_eq!(centre.term(Term::Mobility).white, WEIGHTS.mobility[1] * 8);
+    assert_eq!(corner.term(Term::Mobility).white, WEIGHTS.mobility[1] * 2);
+}
+
+#[test]
+fn king_safety() {
+    let castled = breakdown(&chess("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1"));
+    let exposed = breakdown(&chess("6k1/5ppp/8/8/5PPP/8/8/6K1 w - - 0 1"));
+
+    assert_eq!(
+        castled.term(Term::KingSafety).white,
+        WEIGHTS.pawn_shield * 3
+    );
+    // king safety is only scored in the middlegame
+    assert_eq!(exposed.term(Term::KingSafety).white, Score::ZERO);
+    assert_eq!(castled.value(Term::KingSafety, Color::White), 0);
+
+    // the queen attacks f7, g7 and h7 from the open b1-h7 diagonal and the h-file
+    let attacked = breakdown(&chess("6k1/5p1p/8/8/8/8/5PPP/1Q4K1 b - - 0 1"));
+    assert!(attacked.term(Term::KingSafety).black.mg < castled.term(Term::KingSafety).black.mg);
+}
+
+#[test]
+fn bishop_pair() {
+    let pair = breakdown(&chess("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1"));
+
+    assert_eq!(pair.term(Term::BishopPair).white, WEIGHTS.bishop_pair);
+    assert_eq!(pair.term(Term::BishopPair).black, Score::ZERO);
+}
+
+#[test]
+fn custom_weights() {
+    let material_only = Weights {
+        piece_squares_mg: [[0; 64]; 6],
+        piece_squares_eg: [[0; 64]; 6],
+        doubled_pawn: Score::ZERO,
+        isolated_pawn: Score::ZERO,
+        passed_pawn: [Score::ZERO; 8],
+        mobility: [Score::ZERO; 6],
+        pawn_shield: Score::ZERO,
+        open_file_near_king: Score::ZERO,
+        king_zone_attack: Score::ZERO,
+        bishop_pair: Score::ZERO,
+        ..Weights::default()
+    };
+
+    let chess = chess("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1");
+
+    assert_eq!(
+        breakdown_with(&chess, &material_only).total(),
+        (WEIGHTS.material[2] * 2).taper(2)
+    );
+}
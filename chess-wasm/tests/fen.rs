@@ -0,0 +1,174 @@
+use chess_wasm::chess::*;
+use chess_wasm::errors::*;
+
+#[test]
+fn valid_fen_round_trips() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "8/8/4k3/8/8/3QK3/8/8 b - - 140 312",
+    ];
+
+    let mut chess = Chess::new();
+
+    for fen in fens {
+        assert_eq!(chess.try_load_fen(fen), Ok(()));
+        assert_eq!(chess.get_fen(), fen);
+    }
+}
+
+#[test]
+fn missing_move_counters_default() {
+    let mut chess = Chess::new();
+
+    assert_eq!(chess.try_load_fen("4k3/8/8/8/8/8/8/4K3 w - -"), Ok(()));
+    assert_eq!(chess.get_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+}
+
+#[test]
+fn move_counters_past_255() {
+    let mut chess = Chess::new();
+
+    assert_eq!(
+        chess.try_load_fen("8/8/4k3/8/8/3QK3/8/8 b - - 255 255"),
+        Ok(())
+    );
+
+    chess.move_piece("Ke7").unwrap();
+
+    assert_eq!(chess.half_moves(), 256);
+    assert_eq!(chess.full_moves(), 256);
+    assert_eq!(chess.get_fen(), "8/4k3/8/8/8/3QK3/8/8 w - - 256 256");
+
+    chess.undo();
+    assert_eq!(chess.get_fen(), "8/8/4k3/8/8/3QK3/8/8 b - - 255 255");
+}
+
+#[test]
+fn king_and_rook_moves_advance_the_halfmove_clock() {
+    let mut chess = Chess::new();
+    chess.load_fen("r3k3/8/8/8/8/8/8/R3K3 w - - 0 1".to_string());
+
+    for san in ["Kd1", "Rb8", "Rb1", "Kd8"] {
+        chess.move_piece(san).unwrap();
+    }
+
+    assert_eq!(chess.get_fen(), "1r1k4/8/8/8/8/8/8/1R1K4 w - - 4 3");
+}
+
+#[test]
+fn load_fen_resets_previous_position() {
+    let mut chess = Chess::new();
+
+    chess.load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
+    chess.load_fen("4k3/8/8/8/8/8/8/4K3 b - - 5 40".to_string());
+
+    assert_eq!(chess.get_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 5 40");
+    assert!(chess.is_insufficient_materials());
+}
+
+#[test]
+fn invalid_fen_keeps_current_position() {
+    let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    let mut chess = Chess::new();
+    chess.load_fen(fen.to_string());
+
+    assert!(chess.try_load_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1").is_err());
+    assert_eq!(chess.get_fen(), fen);
+}
+
+#[test]
+fn invalid_pieces() {
+    let cases = [
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+            FenError::WrongRankCount(7),
+        ),
+        (
+            "rnbqkbnr/ppppxppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            FenError::InvalidPiece('x', 7),
+        ),
+        (
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            FenError::InvalidPiece('9', 6),
+        ),
+        (
+            "rnbqkbnr/ppppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            FenError::RankOverflow(7),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/4P4/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1",
+            FenError::RankOverflow(5),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/7/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            FenError::RankUnderflow(5),
+        ),
+        (
+            "rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1",
+            FenError::MissingKing("black"),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKKNR w kq - 0 1",
+            FenError::TooManyKings("white"),
+        ),
+        (
+            "Pnbqkbnr/1ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQk - 0 1",
+            FenError::PawnOnBackRank("a8".to_string()),
+        ),
+    ];
+
+    let mut chess = Chess::new();
+
+    for (fen, error) in cases {
+        assert_eq!(chess.try_load_fen(fen), Err(error), "{}", fen);
+    }
+}
+
+#[test]
+fn invalid_fields() {
+    let cases = [
+        ("4k3/8/8/8/8/8/8/4K3 w", FenError::WrongFieldCount(2)),
+        (
+            "4k3/8/8/8/8/8/8/4K3 white - - 0 1",
+            FenError::InvalidTurn("white".to_string()),
+        ),
+        (
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQxq - 0 1",
+            FenError::InvalidCastlingRights("KQxq".to_string()),
+        ),
+        (
+            "r3k2r/8/8/8/8/8/8/R3K2R w KKq - 0 1",
+            FenError::InvalidCastlingRights("KKq".to_string()),
+        ),
+        (
+            "4k3/8/8/8/4P3/8/8/4K3 b - e9 0 1",
+            FenError::InvalidEnPassantSquare("e9".to_string()),
+        ),
+        (
+            "4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1",
+            FenError::EnPassantWrongRank("e3".to_string()),
+        ),
+        (
+            "4k3/8/8/8/4P3/8/8/4K3 b - e3 -1 1",
+            FenError::InvalidHalfMoves("-1".to_string()),
+        ),
+        (
+            "4k3/8/8/8/4P3/8/8/4K3 b - e3 0 65536",
+            FenError::InvalidFullMoves("65536".to_string()),
+        ),
+        (
+            "4k3/8/8/8/4P3/8/8/4K3 b - e3 0 one",
+            FenError::InvalidFullMoves("one".to_string()),
+        ),
+    ];
+
+    let mut chess = Chess::new();
+
+    for (fen, error) in cases {
+        assert_eq!(chess.try_load_fen(fen), Err(error), "{}", fen);
+    }
+}
@@ -0,0 +1,125 @@
+use chess_wasm::pgn::DEFAULT_POSITION;
+use chess_wasm::wasm::*;
+
+// Errors are thrown as JavaScript exceptions, which only exist on wasm, see web.rs
+
+#[test]
+fn starts_from_the_standard_position() {
+    let mut game = Game::new(None).unwrap();
+
+    assert_eq!(game.fen(), DEFAULT_POSITION);
+    assert_eq!(game.turn(), "w");
+    assert_eq!(game.legal_moves(None).unwrap().len(), 20);
+    assert!(game.history().is_empty());
+    assert_eq!(game.outcome(), None);
+}
+
+#[test]
+fn load_fen() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 3 20";
+    let mut game = Game::new(None).unwrap();
+
+    game.load_fen(fen).unwrap();
+
+    assert_eq!(game.fen(), fen);
+    assert_eq!(game.turn(), "b");
+    assert_eq!(Game::new(Some(fen.to_string())).unwrap().fen(), fen);
+}
+
+#[test]
+fn legal_moves_of_a_square() {
+    let mut game = Game::new(None).unwrap();
+    let moves = game.legal_moves(Some("g1".to_string())).unwrap();
+
+    let sans: Vec<&str> = moves.iter().map(|m| m.san.as_str()).collect();
+    assert_eq!(sans, ["Nf3", "Nh3"]);
+
+    assert!(game.legal_moves(Some("e5".to_string())).unwrap().is_empty());
+}
+
+#[test]
+fn moves_in_san_and_uci() {
+    let mut game = Game::new(None).unwrap();
+
+    let e4 = game.play("e4").unwrap();
+    assert_eq!(
+        e4,
+        GameMove {
+            color: "w".to_string(),
+            piece: "p".to_string(),
+            from: "e2".to_string(),
+            to: "e4".to_string(),
+            san: "e4".to_string(),
+            uci: "e2e4".to_string(),
+            captured: None,
+            promotion: None,
+        }
+    );
+
+    let d5 = game.play("d7d5").unwrap();
+    assert_eq!(d5.san, "d5");
+    assert_eq!(d5.color, "b");
+
+    let capture = game.play("exd5").unwrap();
+    assert_eq!(capture.captured.as_deref(), Some("p"));
+    assert_eq!(capture.uci, "e4d5");
+
+    let history: Vec<String> = game.history().into_iter().map(|m| m.san).collect();
+    assert_eq!(history, ["e4", "d5", "exd5"]);
+}
+
+#[test]
+fn promotion() {
+    let mut game = Game::new(Some("8/4P3/8/8/8/8/k7/4K3 w - - 0 1".to_string())).unwrap();
+
+    let promotion = game.play("e7e8n").unwrap();
+
+    assert_eq!(promotion.san, "e8=N");
+    assert_eq!(promotion.promotion.as_deref(), Some("n"));
+}
+
+#[test]
+fn undo() {
+    let mut game = Game::new(None).unwrap();
+
+    assert_eq!(game.undo(), None);
+
+    game.play("Nf3").unwrap();
+    game.play("e5").unwrap();
+
+    let undone = game.undo().unwrap();
+    assert_eq!(undone.san, "e5");
+    assert_eq!(game.turn(), "b");
+    assert_eq!(game.history().len(), 1);
+}
+
+#[test]
+fn check_and_outcome() {
+    let mut game = Game::new(None).unwrap();
+
+    for san in ["f3", "e5", "g4"] {
+        game.play(san).unwrap();
+    }
+
+    assert!(!game.in_check());
+
+    let mate = game.play("Qh4").unwrap();
+    assert_eq!(mate.san, "Qh4#");
+    assert!(game.in_check());
+    assert_eq!(
+        game.outcome(),
+        Some(GameOutcome {
+            winner: Some("b".to_string()),
+            termination: "checkmate".to_string(),
+        })
+    );
+
+    game.load_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(
+        game.outcome(),
+        Some(GameOutcome {
+            winner: None,
+            termination: "stalemate".to_string(),
+        })
+    );
+}
@@ -0,0 +1,103 @@
+use chess_wasm::chess::*;
+
+mod utils;
+use utils::{chess, play};
+
+fn hash(fen: &str) -> u64 {
+    chess(fen).hash()
+}
+
+/// Compare the incrementally updated key to the key of the same position loaded from FEN
+fn check_tree(chess: &mut Chess, depth: u8) {
+    assert_eq!(chess.hash(), hash(&chess.get_fen()), "{}", chess.get_fen());
+
+    if depth == 0 {
+        return;
+    }
+
+    let before = chess.hash();
+
+    for _move in chess.generate_legal_moves() {
+        chess.make_move(_move).unwrap();
+        check_tree(chess, depth - 1);
+        chess.undo();
+
+        assert_eq!(chess.hash(), before);
+    }
+}
+
+#[test]
+fn incremental_hash_matches_fen() {
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ];
+
+    for fen in fens {
+        let mut chess = chess(fen);
+
+        check_tree(&mut chess, 2);
+    }
+}
+
+#[test]
+fn transpositions_have_the_same_hash() {
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    let a = play(start, &["Nf3", "Nf6", "Nc3"]);
+    let b = play(start, &["Nc3", "Nf6", "Nf3"]);
+    assert_eq!(a.hash(), b.hash());
+
+    // the clocks are not part of the position
+    let c = play(start, &["Nf3", "Nf6", "Ng1", "Ng8"]);
+    assert_eq!(c.hash(), hash(start));
+}
+
+#[test]
+fn hash_follows_fide_position_identity() {
+    // side to move
+    assert_ne!(
+        hash("4k3/8/8/8/8/8/8/4K3 w - - 0 1"),
+        hash("4k3/8/8/8/8/8/8/4K3 b - - 0 1")
+    );
+
+    // castling rights
+    assert_ne!(
+        hash("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"),
+        hash("r3k2r/8/8/8/8/8/8/R3K2R w Qkq - 0 1")
+    );
+
+    // an en passant square only matters if the pawn can be captured
+    assert_eq!(
+        hash("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1"),
+        hash("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1")
+    );
+    assert_ne!(
+        hash("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1"),
+        hash("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1")
+    );
+
+    // not even if the capture would be illegal
+    assert_eq!(
+        hash("8/8/8/8/k2pP2R/8/8/4K3 b - e3 0 1"),
+        hash("8/8/8/8/k2pP2R/8/8/4K3 b - - 0 1")
+    );
+}
+
+#[test]
+fn double_push_decides_en_passant() {
+    let capturable = play("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1", &["e4"]);
+    assert_ne!(capturable.hash(), hash("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1"));
+    assert_eq!(capturable.hash(), hash("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1"));
+
+    let pinned = play("8/8/8/8/k2p3R/8/4P3/4K3 w - - 0 1", &["e4"]);
+    assert_eq!(pinned.hash(), hash("8/8/8/8/k2pP2R/8/8/4K3 b - - 0 1"));
+
+    // the en passant square is gone after any other move
+    let mut chess = play("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1", &["e4", "Kd7"]);
+    assert_eq!(chess.hash(), hash("8/3k4/8/8/3pP3/8/8/4K3 w - - 0 1"));
+
+    chess.undo();
+    assert_eq!(chess.hash(), capturable.hash());
+}
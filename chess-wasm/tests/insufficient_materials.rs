@@ -2,7 +2,7 @@ use chess_wasm::chess::*;
 
 #[test]
 fn is_insufficient_materials() {
-    let insufficient_materials = vec![
+    let insufficient_materials = [
         "8/8/8/8/8/8/8/k6K w - - 0 1",
         "8/2N5/8/8/8/8/8/k6K w - - 0 1",
         "8/2b5/8/8/8/8/8/k6K w - - 0 1",
@@ -10,12 +10,14 @@ fn is_insufficient_materials() {
         "8/b1B1b1B1/1b1B1b1B/8/8/8/8/1k5K w - - 0 1",
     ];
 
-    let not_insufficient_materials = vec![
+    let not_insufficient_materials = [
         "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
         "8/2p5/8/8/8/8/8/k6K w - - 0 1",
         "5k1K/7B/8/6b1/8/8/8/8 b - - 0 1",
         "7K/5k1N/8/6b1/8/8/8/8 b - - 0 1",
         "7K/5k1N/8/4n3/8/8/8/8 b - - 0 1",
+        "4k3/8/8/8/8/8/7p/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
     ];
 
     let mut chess = Chess::new();
@@ -1,4 +1,5 @@
 use chess_wasm::chess::*;
+use chess_wasm::chess2::{self, Color, Role};
 use chess_wasm::errors::*;
 
 #[test]
@@ -81,7 +82,7 @@ fn illegal_move() {
     let mut chess = Chess::new();
     chess.load_fen(fen);
 
-    assert_eq!(chess.move_piece("e5"), Err(MoveError::InvalidPieceToMove));
+    assert_eq!(chess.move_piece("e5"), Err(MoveError::IllegalMove));
 }
 
 #[test]
@@ -108,3 +109,80 @@ fn promotion() {
     assert!(chess.is_draw());
     assert_eq!(chess.get_fen(), next_fen);
 }
+
+#[test]
+fn legal_moves_include_underpromotions() {
+    let mut chess = Chess::new();
+    chess.load_fen("8/k7/8/8/8/8/7p/K7 b - - 0 1".to_string());
+
+    let promotions: Vec<Move> = chess
+        .generate_legal_moves()
+        .into_iter()
+        .filter(|m| m.is_promotion())
+        .collect();
+
+    assert_eq!(promotions.len(), 4);
+
+    let knight = promotions
+        .iter()
+        .find(|m| m.promotion_role() == Some(Role::Knight))
+        .copied()
+        .unwrap();
+
+    assert_eq!(
+        knight.moved_piece(),
+        chess2::Piece::new(Color::Black, Role::Pawn)
+    );
+    assert_eq!(chess.make_move(knight), Ok(()));
+    assert_eq!(chess.get_fen(), "8/k7/8/8/8/8/8/K6n w - - 0 2");
+}
+
+#[test]
+fn move_flags() {
+    let mut chess = Chess::new();
+    chess.load_fen("r3k3/8/8/3pP3/8/8/8/R3K2R w KQq d6 0 1".to_string());
+
+    let moves = chess.generate_legal_moves();
+
+    let en_passant = moves.iter().find(|m| m.is_en_passant()).unwrap();
+    assert!(en_passant.is_capture());
+    assert_eq!(
+        en_passant.captured_piece(),
+        Some(chess2::Piece::new(Color::Black, Role::Pawn))
+    );
+
+    let castles: Vec<MoveFlags> = moves
+        .iter()
+        .filter(|m| m.is_castle())
+        .map(|m| m.flags())
+        .collect();
+    assert_eq!(
+        castles,
+        [MoveFlag::KING_SIDE_CASTLE, MoveFlag::QUEEN_SIDE_CASTLE]
+    );
+
+    let rook_capture = moves
+        .iter()
+        .find(|m| m.captured_piece() == Some(chess2::Piece::new(Color::Black, Role::Rook)))
+        .unwrap();
+    assert_eq!(
+        rook_capture.moved_piece(),
+        chess2::Piece::new(Color::White, Role::Rook)
+    );
+    assert_eq!(rook_capture.flags(), MoveFlag::CAPTURE);
+}
+
+#[test]
+fn make_move_rejects_illegal_moves() {
+    let mut chess = Chess::new();
+    chess.load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
+
+    // a white move, once it is black's turn
+    let _move = chess.generate_legal_moves()[0];
+    chess.move_piece("e4").unwrap();
+
+    let fen = chess.get_fen();
+
+    assert_eq!(chess.make_move(_move), Err(MoveError::IllegalMove));
+    assert_eq!(chess.get_fen(), fen);
+}
@@ -0,0 +1,190 @@
+use chess_wasm::chess::Move;
+use chess_wasm::chess2::Position;
+use chess_wasm::move_picker::*;
+use chess_wasm::pgn::DEFAULT_POSITION;
+
+mod utils;
+use utils::chess;
+
+fn uci(moves: &[Move]) -> Vec<String> {
+    moves.iter().map(Move::to_uci).collect()
+}
+
+/// The moves picked and the stage of each
+fn pick(mut picker: MovePicker) -> Vec<(String, Stage)> {
+    let mut picked = vec![];
+
+    while let Some(_move) = picker.next() {
+        picked.push((_move.to_uci(), picker.stage()));
+    }
+
+    picked
+}
+
+fn see_of(fen: &str, uci: &str) -> i32 {
+    let mut chess = chess(fen);
+    let _move = chess.parse_uci(uci).unwrap();
+
+    see(&Position::from(&chess), _move)
+}
+
+// a pawn takes the queen, a pawn takes a defended pawn, and the queen takes a defended pawn
+const CAPTURES: &str = "4k3/2p5/3p1q2/4P3/8/5N2/3Q4/4K3 w - - 0 1";
+
+#[test]
+fn every_legal_move_once() {
+    let fens = [
+        DEFAULT_POSITION,
+        CAPTURES,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    ];
+
+    for fen in fens.iter() {
+        let mut chess = chess(fen);
+        let mut legal = uci(&chess.generate_legal_moves());
+        let hash_move = chess.generate_legal_moves().last().copied();
+
+        let picker = MovePicker::new(&chess, hash_move, &History::new(), 0, None);
+        assert_eq!(picker.len(), legal.len());
+
+        let mut picked = uci(&picker.collect::<Vec<Move>>());
+
+        legal.sort();
+        picked.sort();
+        assert_eq!(picked, legal, "{}", fen);
+    }
+}
+
+#[test]
+fn stages() {
+    let mut chess = chess(CAPTURES);
+    let hash_move = chess.parse_uci("f3g5").unwrap();
+
+    let picked = pick(MovePicker::new(
+        &chess,
+        Some(hash_move),
+        &History::new(),
+        0,
+        None,
+    ));
+
+    assert_eq!(picked[0], ("f3g5".to_string(), Stage::HashMove));
+    assert_eq!(picked[1], ("e5f6".to_string(), Stage::GoodCaptures));
+    assert_eq!(picked[2], ("e5d6".to_string(), Stage::GoodCaptures));
+    assert_eq!(picked[3].1, Stage::Quiets);
+    assert_eq!(
+        picked[picked.len() - 1],
+        ("d2d6".to_string(), Stage::BadCaptures)
+    );
+
+    let stages: Vec<Stage> = picked.iter().map(|(_, stage)| *stage).collect();
+    assert!(stages.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+#[test]
+fn illegal_hash_move() {
+    let mut start = chess(DEFAULT_POSITION);
+    let e2e4 = start.parse_uci("e2e4").unwrap();
+
+    // the hash move of another position, from a key collision
+    let chess = chess(CAPTURES);
+    let picked = pick(MovePicker::new(
+        &chess,
+        Some(e2e4),
+        &History::new(),
+        0,
+        None,
+    ));
+
+    assert!(picked
+        .iter()
+        .all(|(uci, stage)| uci != "e2e4" && *stage != Stage::HashMove));
+}
+
+#[test]
+fn killers_counter_moves_and_history() {
+    let mut chess = chess(CAPTURES);
+    let mut history = History::new();
+
+    let mut black = self::chess("4k3/2p5/3p1q2/4P3/8/5N2/3Q4/4K3 b - - 0 1");
+    let previous = black.parse_uci("f6f5").unwrap();
+
+    let g5 = chess.parse_uci("f3g5").unwrap();
+    let h4 = chess.parse_uci("f3h4").unwrap();
+    let d3 = chess.parse_uci("d2d3").unwrap();
+    let e2 = chess.parse_uci("e1e2").unwrap();
+
+    // g5 is a killer at ply 0, h4 a killer at ply 1 and the last refutation of f5
+    history.record_cutoff(0, 3, None, g5, &[]);
+    history.record_cutoff(1, 3, Some(previous), h4, &[]);
+    // d3 caused a cutoff after e2 didn't
+    history.record_cutoff(5, 4, None, d3, &[e2]);
+
+    assert_eq!(history.killers(0), [Some(g5), None]);
+    assert_eq!(history.counter_move(previous), Some(h4));
+    assert!(history.score(d3) > 0 && history.score(e2) < 0);
+
+    let picked = pick(MovePicker::new(&chess, None, &history, 0, Some(previous)));
+    let stage_of = |uci: &str| picked.iter().find(|(m, _)| m == uci).unwrap().1;
+
+    assert_eq!(stage_of("f3g5"), Stage::Killers);
+    assert_eq!(stage_of("f3h4"), Stage::CounterMove);
+
+    let quiets: Vec<&str> = picked
+        .iter()
+        .filter(|(_, stage)| *stage == Stage::Quiets)
+        .map(|(uci, _)| uci.as_str())
+        .collect();
+
+    // by the history scores, e2 below the moves that were never tried
+    assert_eq!(quiets[0], "d2d3");
+    assert_eq!(quiets[quiets.len() - 1], "e1e2");
+
+    // a new search forgets the killers, not the counter-moves
+    history.new_search();
+    assert_eq!(history.killers(0), [None, None]);
+    assert_eq!(history.counter_move(previous), Some(h4));
+    assert!(history.score(d3) > 0);
+}
+
+#[test]
+fn tactical() {
+    let chess = chess(CAPTURES);
+    let picker = MovePicker::tactical(&chess, None);
+
+    assert!(picker.has_legal_moves());
+    assert_eq!(
+        pick(picker),
+        [
+            ("e5f6".to_string(), Stage::GoodCaptures),
+            ("e5d6".to_string(), Stage::GoodCaptures),
+            ("d2d6".to_string(), Stage::BadCaptures),
+        ]
+    );
+
+    // stalemate
+    let chess = self::chess("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+    let picker = MovePicker::tactical(&chess, None);
+    assert!(!picker.has_legal_moves());
+    assert_eq!(picker.count(), 0);
+}
+
+#[test]
+fn static_exchange_evaluation() {
+    // an undefended pawn
+    assert_eq!(see_of("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5"), 100);
+    // a pawn defended by a pawn
+    assert_eq!(see_of(CAPTURES, "d2d6"), -850);
+    assert_eq!(see_of(CAPTURES, "e5d6"), 0);
+    // a rook defended by a rook, with a second rook behind the first
+    assert_eq!(see_of("3rk3/8/3p4/8/8/8/3R4/4K3 w - - 0 1", "d2d6"), -400);
+    assert_eq!(see_of("3rk3/8/3p4/8/8/8/3R4/3RK3 w - - 0 1", "d2d6"), 100);
+    // en passant
+    assert_eq!(see_of("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+    // a promotion on a defended square
+    assert_eq!(see_of("3rk3/2P5/8/8/8/8/8/4K3 w - - 0 1", "c7c8q"), -100);
+    // the king only takes back if nothing defends the knight
+    assert_eq!(see_of("4k3/8/8/1n2K3/R2p4/8/8/8 w - - 0 1", "a4d4"), -80);
+    assert_eq!(see_of("4k3/b7/8/1n2K3/R2p4/8/8/8 w - - 0 1", "a4d4"), -400);
+}
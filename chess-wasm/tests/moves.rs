@@ -1,4 +1,5 @@
 use chess_wasm::chess::*;
+use chess_wasm::chess2::Square;
 mod utils;
 use utils::compare_vec;
 
@@ -11,7 +12,7 @@ fn moves_for_pawn() {
 
     let moves = ["e3", "e4"];
 
-    assert_eq!(chess.moves("e2"), moves);
+    assert_eq!(chess.moves(Square::E2), moves);
 }
 
 // #[test]
@@ -35,10 +36,9 @@ fn moves_for_pinned_piece() {
 
     let moves: [&str; 0] = [];
 
-    assert_eq!(chess.moves("c3"), moves);
+    assert_eq!(chess.moves(Square::C3), moves);
 }
 
-// TODO: fix this later, include promotions in moves
 #[test]
 fn moves_for_promotion() {
     let fen = "8/k7/8/8/8/8/7p/K7 b - - 0 1".to_string();
@@ -46,10 +46,9 @@ fn moves_for_promotion() {
     let mut chess = Chess::new();
     chess.load_fen(fen);
 
-    // TODO be more explicit
-    let moves = ["h1"];
+    let moves = ["h1=Q+", "h1=R+", "h1=B", "h1=N"];
 
-    assert_eq!(chess.moves("h2"), moves);
+    assert_eq!(chess.moves(Square::H2), moves);
 }
 
 #[test]
@@ -61,7 +60,7 @@ fn castling() {
 
     let moves = ["Kf1", "Kd1", "O-O", "O-O-O"];
 
-    assert_eq!(chess.moves("e1"), moves);
+    assert_eq!(chess.moves(Square::E1), moves);
 }
 
 #[test]
@@ -73,7 +72,7 @@ fn no_castling() {
 
     let moves = ["Kf1", "Kd1"];
 
-    assert_eq!(chess.moves("e1"), moves);
+    assert_eq!(chess.moves(Square::E1), moves);
 }
 
 #[test]
@@ -85,7 +84,7 @@ fn king_trapped_cant_move() {
 
     let moves: [&str; 0] = [];
 
-    assert_eq!(chess.moves("a3"), moves);
+    assert_eq!(chess.moves(Square::A3), moves);
 }
 
 #[test]
@@ -97,7 +96,7 @@ fn knight_moves() {
 
     let moves = ["Na3".to_string(), "Nc3".to_string()].to_vec();
 
-    assert!(compare_vec(&chess.moves("b1"), &moves));
+    assert!(compare_vec(&chess.moves(Square::B1), &moves));
 }
 
 #[test]
@@ -110,7 +109,7 @@ fn en_passant() {
 
     let moves = ["axb6".to_string()].to_vec();
 
-    assert!(compare_vec(&chess.moves("a5"), &moves));
+    assert!(compare_vec(&chess.moves(Square::A5), &moves));
 }
 
 #[test]
@@ -130,5 +129,5 @@ fn queen() {
     ]
     .to_vec();
 
-    assert!(compare_vec(&chess.moves("e2"), &moves));
+    assert!(compare_vec(&chess.moves(Square::E2), &moves));
 }
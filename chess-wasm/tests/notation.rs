@@ -0,0 +1,197 @@
+use chess_wasm::chess::*;
+use chess_wasm::chess2::{self, Color, Role};
+use chess_wasm::errors::*;
+
+fn san_moves(fen: &str) -> Vec<String> {
+    let mut chess = Chess::new();
+    chess.load_fen(fen.to_string());
+
+    chess
+        .generate_legal_moves()
+        .into_iter()
+        .map(|m| chess.san(m))
+        .collect()
+}
+
+#[test]
+fn file_disambiguation() {
+    let moves = san_moves("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1");
+
+    assert!(moves.contains(&"Nbd2".to_string()));
+    assert!(moves.contains(&"Nfd2".to_string()));
+    assert!(moves.contains(&"Nc3".to_string()));
+}
+
+#[test]
+fn rank_disambiguation() {
+    let moves = san_moves("7k/8/8/8/4R3/8/8/4R1K1 w - - 0 1");
+
+    assert!(moves.contains(&"R1e2".to_string()));
+    assert!(moves.contains(&"R4e2".to_string()));
+    assert!(moves.contains(&"Ra1".to_string()));
+}
+
+#[test]
+fn square_disambiguation() {
+    let moves = san_moves("2k5/8/8/8/4Q2Q/8/8/K6Q w - - 0 1");
+
+    assert!(moves.contains(&"Qh4e1".to_string()));
+    assert!(moves.contains(&"Qee1".to_string()));
+    assert!(moves.contains(&"Q1e1".to_string()));
+}
+
+#[test]
+fn pinned_piece_does_not_disambiguate() {
+    let moves = san_moves("r2qkbnr/ppp2ppp/2n5/1B2pQ2/4P3/8/PPP2PPP/RNB1K2R b KQkq - 3 7");
+
+    assert!(moves.contains(&"Ne7".to_string()));
+    assert!(!moves.contains(&"Nge7".to_string()));
+}
+
+#[test]
+fn pawn_captures_and_promotions() {
+    let moves = san_moves("1n2k3/P7/8/3pP3/8/8/8/4K3 w - d6 0 1");
+
+    for san in ["exd6", "e6", "axb8=Q+", "axb8=N", "a8=R", "a8=B"] {
+        assert!(moves.contains(&san.to_string()), "{}", san);
+    }
+}
+
+#[test]
+fn castling_check_and_checkmate() {
+    let moves = san_moves("5k2/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+
+    assert!(moves.contains(&"O-O+".to_string()));
+    assert!(moves.contains(&"O-O-O".to_string()));
+    assert!(moves.contains(&"Ra8+".to_string()));
+
+    let moves = san_moves("7k/3R4/3p2Q1/6Q1/2N1N3/8/8/3R3K w - - 0 1");
+
+    assert!(moves.contains(&"Rd8#".to_string()));
+    assert!(moves.contains(&"Rh7#".to_string()));
+}
+
+#[test]
+fn parse_san_accepts_suffixes_and_zero_castling() {
+    let mut chess = Chess::new();
+    chess.load_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".to_string());
+
+    let castle = chess.parse_san("0-0!?").unwrap();
+    assert_eq!(castle.flags(), MoveFlag::KING_SIDE_CASTLE);
+
+    let check = chess.parse_san("Ra8+").unwrap();
+    assert_eq!(
+        check.captured_piece(),
+        Some(chess2::Piece::new(Color::Black, Role::Rook))
+    );
+    assert_eq!(chess.parse_san("Rxa8+!"), Ok(check));
+}
+
+#[test]
+fn parse_san_errors() {
+    let mut chess = Chess::new();
+    chess.load_fen("4k3/P7/8/8/8/8/8/1N2KN2 w - - 0 1".to_string());
+
+    let cases = [
+        ("Nf9", MoveError::InvalidNotation("Nf9".to_string())),
+        ("exd", MoveError::InvalidNotation("exd".to_string())),
+        ("Zf3", MoveError::InvalidNotation("Zf3".to_string())),
+        ("a8Q", MoveError::InvalidNotation("a8Q".to_string())),
+        ("nc3", MoveError::InvalidNotation("nc3".to_string())),
+        ("a8", MoveError::InvalidPromotion),
+        ("Nd2", MoveError::AmbiguousMoveNotation),
+        ("Nxc3", MoveError::IllegalCapture),
+        ("Nc4", MoveError::IllegalMove),
+        ("O-O", MoveError::IllegalKingSideCastle),
+    ];
+
+    for (san, error) in cases {
+        assert_eq!(chess.parse_san(san), Err(error), "{}", san);
+    }
+}
+
+#[test]
+fn parse_san_lenient() {
+    let mut chess = Chess::new();
+    chess.load_fen("1b2k3/P7/8/8/8/2p5/1P6/1N2KB2 w - - 0 1".to_string());
+
+    let cases = [
+        ("a8Q", "a8=Q"),
+        ("a8", "a8=Q"),
+        ("axb8n", "axb8=N"),
+        ("nd2", "Nd2"),
+        ("bxc3", "bxc3"),
+        ("b2-b4", "b4"),
+        ("Bf1-d3", "Bd3"),
+    ];
+
+    for (sloppy, san) in cases {
+        let _move = chess.parse_san_lenient(sloppy).unwrap();
+        assert_eq!(chess.san(_move), san, "{}", sloppy);
+    }
+
+    assert_eq!(
+        chess.parse_san_lenient("o-o"),
+        Err(MoveError::IllegalKingSideCastle)
+    );
+
+    chess.load_fen("4k3/8/8/8/8/2p5/1B6/4K3 w - - 0 1".to_string());
+
+    let bishop = chess.parse_san_lenient("bxc3").unwrap();
+    assert_eq!(
+        bishop.moved_piece(),
+        chess2::Piece::new(Color::White, Role::Bishop)
+    );
+}
+
+#[test]
+fn uci_moves() {
+    let mut chess = Chess::new();
+    chess.load_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1".to_string());
+
+    let castle = chess.parse_uci("e1c1").unwrap();
+    assert_eq!(castle.flags(), MoveFlag::QUEEN_SIDE_CASTLE);
+    assert_eq!(castle.to_uci(), "e1c1");
+
+    let promotion = chess.make_uci_move("b7a8n").unwrap();
+    assert_eq!(promotion.promotion_role(), Some(Role::Knight));
+    assert_eq!(promotion.to_uci(), "b7a8n");
+
+    assert!(chess.make_uci_move("e8g8").is_ok());
+    assert_eq!(chess.get_fen(), "N4rk1/8/8/8/8/8/8/R3K2R w KQ - 1 2");
+}
+
+#[test]
+fn uci_errors() {
+    let mut chess = Chess::new();
+    chess.load_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1".to_string());
+
+    let cases = [
+        ("e2", MoveError::InvalidNotation("e2".to_string())),
+        ("e1i1", MoveError::InvalidNotation("e1i1".to_string())),
+        ("b7b8k", MoveError::InvalidNotation("b7b8k".to_string())),
+        ("e8e7", MoveError::IllegalMove),
+        ("e1e3", MoveError::IllegalMove),
+        ("b7b8", MoveError::InvalidPromotion),
+    ];
+
+    for (uci, error) in cases {
+        assert_eq!(chess.parse_uci(uci), Err(error), "{}", uci);
+    }
+}
+
+#[test]
+fn replay_uci_principal_variation() {
+    let mut chess = Chess::new();
+    chess.load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
+
+    for uci in "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6".split(' ') {
+        let _move = chess.make_uci_move(uci).unwrap();
+        assert_eq!(_move.to_uci(), uci);
+    }
+
+    assert_eq!(
+        chess.get_fen(),
+        "r1bqkbnr/1ppp1ppp/p1n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 4"
+    );
+}
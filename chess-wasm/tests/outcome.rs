@@ -0,0 +1,159 @@
+use chess_wasm::chess2::Color;
+use chess_wasm::outcome::{Outcome, Termination};
+use chess_wasm::pgn::DEFAULT_POSITION;
+
+mod utils;
+use utils::play;
+
+#[test]
+fn game_in_progress() {
+    let chess = play(DEFAULT_POSITION, &["e4", "e5"]);
+
+    assert_eq!(chess.outcome(), None);
+}
+
+#[test]
+fn checkmate() {
+    let chess = play(DEFAULT_POSITION, &["f3", "e5", "g4", "Qh4"]);
+
+    assert_eq!(
+        chess.outcome(),
+        Some(Outcome::win(Color::Black, Termination::Checkmate))
+    );
+}
+
+#[test]
+fn automatic_draws() {
+    let draws = [
+        ("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", Termination::Stalemate),
+        (
+            "8/8/4k3/8/8/3NK3/8/8 w - - 0 1",
+            Termination::InsufficientMaterial,
+        ),
+        (
+            "8/8/4k3/8/8/3QK3/8/8 w - - 150 120",
+            Termination::SeventyFiveMoves,
+        ),
+    ];
+
+    for (fen, termination) in draws.iter() {
+        assert_eq!(
+            play(fen, &[]).outcome(),
+            Some(Outcome::draw(*termination)),
+            "{}",
+            fen
+        );
+    }
+
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+    let moves: Vec<&str> = shuffle.iter().cycle().take(16).copied().collect();
+    let chess = play(DEFAULT_POSITION, &moves);
+
+    assert_eq!(
+        chess.outcome(),
+        Some(Outcome::draw(Termination::FivefoldRepetition))
+    );
+}
+
+#[test]
+fn seventy_five_move_rule() {
+    let mut chess = play("8/8/4k3/8/8/3QK3/8/8 w - - 148 300", &["Qd4"]);
+
+    assert!(chess.is_50_moves_rule());
+    assert!(!chess.is_75_moves_rule());
+    assert_eq!(chess.outcome(), None);
+
+    chess.move_piece("Ke7").unwrap();
+
+    assert!(chess.is_75_moves_rule());
+    assert_eq!(
+        chess.outcome(),
+        Some(Outcome::draw(Termination::SeventyFiveMoves))
+    );
+}
+
+#[test]
+fn checkmate_beats_the_seventy_five_move_rule() {
+    let chess = play("7k/8/6K1/8/8/8/8/R7 w - - 149 120", &["Ra8"]);
+
+    assert_eq!(
+        chess.outcome(),
+        Some(Outcome::win(Color::White, Termination::Checkmate))
+    );
+}
+
+#[test]
+fn claimed_draws() {
+    let mut chess = play(
+        DEFAULT_POSITION,
+        &["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6"],
+    );
+
+    assert!(!chess.claim_draw());
+    assert_eq!(chess.outcome(), None);
+
+    assert!(!chess.is_draw());
+
+    chess.move_piece("Ng1").unwrap();
+    chess.move_piece("Ng8").unwrap();
+
+    assert_eq!(chess.outcome(), None);
+    assert!(chess.is_draw());
+    assert!(chess.claim_draw());
+    assert_eq!(
+        chess.outcome(),
+        Some(Outcome::draw(Termination::ThreefoldRepetition))
+    );
+
+    let mut chess = play("8/8/4k3/8/8/3QK3/8/8 w - - 100 80", &[]);
+
+    assert_eq!(chess.outcome(), None);
+    assert!(chess.claim_draw());
+    assert_eq!(
+        chess.outcome(),
+        Some(Outcome::draw(Termination::FiftyMoves))
+    );
+}
+
+#[test]
+fn declared_outcomes() {
+    let mut chess = play(DEFAULT_POSITION, &["e4"]);
+    chess.resign(Color::Black);
+    assert_eq!(
+        chess.outcome(),
+        Some(Outcome::win(Color::White, Termination::Resignation))
+    );
+
+    let mut chess = play(DEFAULT_POSITION, &["e4"]);
+    chess.lose_on_time(Color::White);
+    assert_eq!(
+        chess.outcome(),
+        Some(Outcome::win(Color::Black, Termination::Timeout))
+    );
+
+    let mut chess = play(DEFAULT_POSITION, &["e4"]);
+    chess.agree_draw();
+    assert_eq!(chess.outcome(), Some(Outcome::draw(Termination::Agreement)));
+
+    chess.undo();
+    assert_eq!(chess.outcome(), None);
+}
+
+#[test]
+fn declaring_after_the_game_is_over() {
+    let mut chess = play(DEFAULT_POSITION, &["f3", "e5", "g4", "Qh4"]);
+    chess.resign(Color::Black);
+
+    assert_eq!(
+        chess.outcome(),
+        Some(Outcome::win(Color::Black, Termination::Checkmate))
+    );
+}
+
+#[test]
+fn pgn_result_follows_the_outcome() {
+    let mut chess = play(DEFAULT_POSITION, &["e4", "e5"]);
+    chess.resign(Color::White);
+
+    assert!(chess.pgn(&[]).contains("[Result \"0-1\"]"));
+}
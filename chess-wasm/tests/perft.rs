@@ -0,0 +1,228 @@
+use chess_wasm::chess::*;
+use chess_wasm::perft::*;
+use chess_wasm::pgn::DEFAULT_POSITION;
+use std::collections::BTreeMap;
+use std::io;
+use std::process::Command;
+
+// https://www.chessprogramming.org/Perft_Results
+fn perft(fen: &str, depth: u8) -> u64 {
+    let mut chess = Chess::new();
+    chess.load_fen(fen.to_string());
+
+    chess.perft(depth)
+}
+
+#[test]
+fn initial_position() {
+    let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    assert_eq!(perft(fen, 1), 20);
+    assert_eq!(perft(fen, 2), 400);
+    assert_eq!(perft(fen, 3), 8902);
+}
+
+#[test]
+fn kiwipete() {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    assert_eq!(perft(fen, 1), 48);
+    assert_eq!(perft(fen, 2), 2039);
+}
+
+#[test]
+fn position_3() {
+    let fen = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+
+    assert_eq!(perft(fen, 1), 14);
+    assert_eq!(perft(fen, 2), 191);
+    assert_eq!(perft(fen, 3), 2812);
+}
+
+#[test]
+fn position_4() {
+    let fen = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+
+    assert_eq!(perft(fen, 1), 6);
+    assert_eq!(perft(fen, 2), 264);
+    assert_eq!(perft(fen, 3), 9467);
+}
+
+#[test]
+fn position_5() {
+    let fen = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+
+    assert_eq!(perft(fen, 1), 44);
+    assert_eq!(perft(fen, 2), 1486);
+}
+
+#[test]
+fn divide_counts_each_promotion() {
+    let mut chess = Chess::new();
+    chess.load_fen("8/1k5P/8/8/8/8/8/1K6 w - - 0 1".to_string());
+
+    let divide = Perft::divide(&mut chess, 1);
+
+    for promotion in ["h7h8q", "h7h8r", "h7h8b", "h7h8n"] {
+        assert_eq!(
+            divide.child_count().get(promotion),
+            Some(&1),
+            "{}",
+            promotion
+        );
+    }
+}
+
+/// An engine that doesn't know about en passant, to check that `compare` finds the position
+/// where it goes wrong
+struct NoEnPassant;
+
+fn count_without_en_passant(chess: &mut Chess, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves: Vec<Move> = chess
+        .generate_legal_moves()
+        .into_iter()
+        .filter(|m| !m.is_en_passant())
+        .collect();
+
+    moves
+        .into_iter()
+        .map(|_move| {
+            chess.make_move(_move).unwrap();
+            let count = count_without_en_passant(chess, depth - 1);
+            chess.undo();
+            count
+        })
+        .sum()
+}
+
+impl Engine for NoEnPassant {
+    fn perft(&mut self, fen: &str, moves: &[String], depth: u8) -> io::Result<Perft> {
+        let mut chess = Chess::new();
+        chess.load_fen(fen.to_string());
+
+        for uci in moves {
+            chess.make_uci_move(uci).unwrap();
+        }
+
+        let mut child_count = BTreeMap::new();
+
+        for _move in chess.generate_legal_moves() {
+            if !_move.is_en_passant() {
+                chess.make_move(_move).unwrap();
+                child_count.insert(
+                    _move.to_uci(),
+                    count_without_en_passant(&mut chess, depth - 1),
+                );
+                chess.undo();
+            }
+        }
+
+        Ok(Perft::new(child_count.values().sum(), child_count))
+    }
+}
+
+#[test]
+fn divide() {
+    let mut chess = Chess::new();
+    chess.load_fen(DEFAULT_POSITION.to_string());
+
+    let perft = Perft::divide(&mut chess, 3);
+
+    assert_eq!(perft.total_count(), 8902);
+    assert_eq!(perft.child_count().len(), 20);
+    assert_eq!(perft.child_count().get("e2e4"), Some(&600));
+    assert_eq!(chess.get_fen(), DEFAULT_POSITION);
+}
+
+#[test]
+fn compare_drills_down_to_the_first_difference() {
+    let fen = "4k3/8/8/8/4p3/8/3P4/4K3 w - - 0 1";
+    let mut chess = Chess::new();
+    chess.load_fen(fen.to_string());
+
+    let comparison = compare(&mut chess, 3, &mut NoEnPassant).unwrap();
+    let mismatch = comparison.mismatch.unwrap();
+
+    assert_ne!(comparison.chess, comparison.engine);
+    assert_eq!(mismatch.fen, "4k3/8/8/8/3Pp3/8/8/4K3 b - d3 0 1");
+    assert_eq!(mismatch.moves, ["d2d4"]);
+    assert_eq!(mismatch.depth, 2);
+    assert_eq!(mismatch.extra_moves(), ["e4d3"]);
+    assert!(mismatch.missing_moves().is_empty());
+
+    // the position is left as it was
+    assert_eq!(chess.get_fen(), fen);
+
+    chess.load_fen("4k3/8/8/8/8/8/3P4/4K3 w - - 0 1".to_string());
+    assert_eq!(
+        compare(&mut chess, 3, &mut NoEnPassant).unwrap().mismatch,
+        None
+    );
+}
+
+const MOCK_ENGINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/utils/mock_engine.sh");
+
+#[test]
+fn uci_engine() {
+    let fen = "k7/8/8/8/8/8/8/K7 w - - 0 1";
+    let mut engine = UciEngine::new(MOCK_ENGINE, &[]).unwrap();
+
+    let perft = engine.perft(fen, &[], 2).unwrap();
+    assert_eq!(perft.total_count(), 10);
+    assert_eq!(perft.child_count().get("a1b2"), Some(&4));
+
+    let mut chess = Chess::new();
+    chess.load_fen(fen.to_string());
+
+    let mismatch = compare(&mut chess, 2, &mut engine)
+        .unwrap()
+        .mismatch
+        .unwrap();
+    assert_eq!(mismatch.fen, "k7/8/8/8/8/8/1K6/8 b - - 1 1");
+    assert_eq!(mismatch.moves, ["a1b2"]);
+    assert_eq!(mismatch.missing_moves(), ["a8a6"]);
+
+    assert_eq!(compare(&mut chess, 1, &mut engine).unwrap().mismatch, None);
+}
+
+#[test]
+fn perft_binary() {
+    let perft = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_perft"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = perft(&["2"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.contains("e2e4: 20"), "{}", stdout);
+    assert!(stdout.ends_with("Nodes searched: 400\n"), "{}", stdout);
+
+    let output = perft(&["--hash", "1", "3"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("e2e4: 600"), "{}", stdout);
+    assert!(stdout.ends_with("Nodes searched: 8902\n"), "{}", stdout);
+
+    let fen = "k7/8/8/8/8/8/8/K7 w - - 0 1";
+
+    let output = perft(&["--engine", MOCK_ENGINE, "1", fen]);
+    assert!(output.status.success());
+
+    let output = perft(&["--engine", MOCK_ENGINE, "2", fen]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stdout.contains("fen: k7/8/8/8/8/8/1K6/8 b - - 1 1\nmoves: a1b2\nmissing moves: a8a6"),
+        "{}",
+        stdout
+    );
+
+    assert_eq!(perft(&["deep"]).status.code(), Some(2));
+    assert_eq!(perft(&["--hash", "big", "2"]).status.code(), Some(2));
+}
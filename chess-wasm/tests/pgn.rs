@@ -0,0 +1,351 @@
+use chess_wasm::chess::*;
+use chess_wasm::errors::*;
+use chess_wasm::pgn::*;
+
+const GAME: &str = r#"[Event "F/S Return Match"]
+[Site "Belgrade, Serbia JUG"]
+[Date "1992.11.04"]
+[Round "29"]
+[White "Fischer, Robert J."]
+[Black "Spassky, Boris V."]
+[Result "1/2-1/2"]
+[Annotator "Someone \"quoted\""]
+
+{Opening comment} 1. e4 e5 2. Nf3 Nc6 3. Bb5 {This opening is called the Ruy Lopez.} 3... a6
+4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
+11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15. Nb1 h6 16. Bh4 c5 17. dxe5
+Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21. Nc4 Nxc4 22. Bxc4 Nb6
+23. Ne5 Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7 27. Qe3 Qg5 28. Qxg5
+hxg5 29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33. f3 Bc8 34. Kf2 Bf5
+35. Ra7 g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5 40. Rd6 Kc5 41. Ra6
+Nf2 42. g4 Bd3 43. Re6 1/2-1/2
+"#;
+
+#[test]
+fn parse_headers_and_main_line() {
+    let game: Game = GAME.parse().unwrap();
+
+    assert_eq!(game.header("White"), Some("Fischer, Robert J."));
+    assert_eq!(game.header("Annotator"), Some("Someone \"quoted\""));
+    assert_eq!(game.header("ECO"), None);
+    assert_eq!(game.headers.len(), 8);
+
+    assert_eq!(game.result, GameResult::Draw);
+    assert_eq!(game.moves.comments, ["Opening comment"]);
+    assert_eq!(game.moves.moves.len(), 85);
+    assert_eq!(
+        game.moves.moves[4].comments,
+        ["This opening is called the Ruy Lopez."]
+    );
+
+    let mut chess = Chess::new();
+    assert_eq!(game.replay(&mut chess), Ok(()));
+    assert_eq!(
+        chess.get_fen(),
+        "8/8/4R1p1/2k3p1/1p4P1/1P1b1P2/3K1n2/8 b - - 2 43"
+    );
+}
+
+#[test]
+fn parse_variations_nags_and_comments() {
+    let pgn = "1. e4 $1 e5!? (1... c5 ; Sicilian\n 2. Nf3 (2. c3 {Alapin}) d6) 2. Nf3 *";
+
+    let game: Game = pgn.parse().unwrap();
+
+    assert_eq!(game.result, GameResult::Unknown);
+
+    let moves = &game.moves.moves;
+    assert_eq!(moves.len(), 3);
+    assert_eq!(moves[0].nags, [1]);
+    assert_eq!(moves[1].nags, [5]);
+
+    let sicilian = &moves[1].variations[0];
+    assert_eq!(sicilian.moves[0].san, "c5");
+    assert_eq!(sicilian.moves[0].comments, ["Sicilian"]);
+    assert_eq!(sicilian.moves[1].variations[0].moves[0].san, "c3");
+    assert_eq!(
+        sicilian.moves[1].variations[0].moves[0].comments,
+        ["Alapin"]
+    );
+
+    let mut chess = Chess::new();
+    assert_eq!(game.replay(&mut chess), Ok(()));
+    assert_eq!(
+        chess.get_fen(),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+    );
+}
+
+#[test]
+fn replay_from_fen_tag() {
+    let pgn = r#"[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"]
+
+1. e4 Kd7 2. e5 Ke6 0-1"#;
+
+    let game: Game = pgn.parse().unwrap();
+    assert_eq!(game.result, GameResult::BlackWins);
+
+    let mut chess = Chess::new();
+    assert_eq!(game.replay(&mut chess), Ok(()));
+    assert_eq!(chess.get_fen(), "8/8/4k3/4P3/8/8/8/4K3 w - - 1 3");
+}
+
+#[test]
+fn replay_reports_the_offending_ply() {
+    let mut chess = Chess::new();
+
+    let game: Game = "1. e4 e5 2. Nf3 Nc6 3. Bb6 a6 *".parse().unwrap();
+    assert_eq!(
+        game.replay(&mut chess),
+        Err(PgnError::IllegalMove {
+            ply: 5,
+            san: "Bb6".to_string(),
+            error: MoveError::IllegalMove,
+        })
+    );
+
+    let game: Game = "1. e4 e5 (1... e4) *".parse().unwrap();
+    assert_eq!(
+        game.replay(&mut chess),
+        Err(PgnError::IllegalMove {
+            ply: 2,
+            san: "e4".to_string(),
+            error: MoveError::IllegalMove,
+        })
+    );
+}
+
+#[test]
+fn syntax_errors() {
+    let cases = [
+        ("[Event \"x]\n1. e4 *", 1),
+        ("[Event x]\n1. e4 *", 1),
+        ("1. e4 {never closed\n\n e5 *", 1),
+        ("1. e4\ne5 (1... d5\n2. d4 *", 3),
+        ("1. e4 e5)", 1),
+        ("( 1. e4 ) *", 1),
+        ("1. e4 e5 *\n2. Nf3", 2),
+        ("1. e4 & e5 *", 1),
+    ];
+
+    for (pgn, line) in cases {
+        match pgn.parse::<Game>() {
+            Err(PgnError::Syntax { line: l, .. }) => assert_eq!(l, line, "{}", pgn),
+            other => panic!("{}: {:?}", pgn, other),
+        }
+    }
+}
+
+#[test]
+fn export_played_game() {
+    let mut chess = Chess::new();
+    chess.load_fen(DEFAULT_POSITION.to_string());
+
+    for san in ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"] {
+        assert!(chess.move_piece(san).is_ok(), "{}", san);
+    }
+
+    let pgn = chess.pgn(&[("White", "Me"), ("Black", "You"), ("Opening", "Scholar's")]);
+
+    assert_eq!(
+        pgn,
+        r#"[Event "?"]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "Me"]
+[Black "You"]
+[Result "1-0"]
+[Opening "Scholar's"]
+
+1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0
+"#
+    );
+}
+
+#[test]
+fn export_claimable_draws() {
+    let mut chess = Chess::new();
+    chess.load_fen(DEFAULT_POSITION.to_string());
+
+    for san in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"] {
+        assert!(chess.move_piece(san).is_ok(), "{}", san);
+    }
+
+    assert_eq!(chess.outcome(), None);
+    assert_eq!(chess.to_game(&[]).result, GameResult::Draw);
+
+    chess.load_fen("8/8/4k3/8/8/3QK3/8/8 w - - 100 80".to_string());
+
+    let game = chess.to_game(&[("Result", "1-0")]);
+
+    assert_eq!(game.result, GameResult::Draw);
+    assert!(game.to_string().ends_with("\n\n1/2-1/2\n"));
+}
+
+#[test]
+fn export_without_a_loaded_position() {
+    let game = Chess::new().to_game(&[]);
+
+    assert_eq!(game.header("FEN"), None);
+    assert!(game.moves.moves.is_empty());
+}
+
+#[test]
+fn export_from_fen() {
+    let fen = "4k3/P7/8/8/8/8/7p/4K3 b - - 3 40";
+
+    let mut chess = Chess::new();
+    chess.load_fen(fen.to_string());
+
+    for san in ["Kd7", "a8=N", "Kc6"] {
+        assert!(chess.move_piece(san).is_ok(), "{}", san);
+    }
+
+    let game = chess.to_game(&[("Result", "0-1"), ("FEN", "ignored")]);
+
+    assert_eq!(game.header("SetUp"), Some("1"));
+    assert_eq!(game.header("FEN"), Some(fen));
+    assert_eq!(game.result, GameResult::BlackWins);
+    assert!(game
+        .to_string()
+        .ends_with("\n\n40... Kd7 41. a8=N Kc6 0-1\n"));
+
+    let mut replayed = Chess::new();
+    assert_eq!(game.replay(&mut replayed), Ok(()));
+    assert_eq!(replayed.get_fen(), chess.get_fen());
+}
+
+#[test]
+fn export_wraps_and_round_trips() {
+    let game: Game = GAME.parse().unwrap();
+
+    let mut chess = Chess::new();
+    assert_eq!(game.replay(&mut chess), Ok(()));
+
+    let pgn = chess.pgn(&[("Result", "1/2-1/2")]);
+
+    assert!(pgn.lines().all(|line| line.len() <= 80));
+    assert!(pgn.lines().any(|line| line.len() > 70));
+
+    let exported: Game = pgn.parse().unwrap();
+    let sans =
+        |game: &Game| -> Vec<String> { game.moves.moves.iter().map(|m| m.san.clone()).collect() };
+    assert_eq!(sans(&exported), sans(&game));
+    assert_eq!(exported.result, GameResult::Draw);
+
+    let game: Game = "{start} 1. e4 $1 {good} e5 (1... c5 {Sicilian} 2. Nf3) (1... e6) 2. Nf3 *"
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        game.to_string(),
+        "{start} 1. e4 $1 {good} 1... e5 (1... c5 {Sicilian} 2. Nf3) (1... e6) 2. Nf3 *\n"
+    );
+    assert_eq!(game.to_string().parse::<Game>(), Ok(game));
+}
+
+const DATABASE: &str = r#"Downloaded from somewhere, 3 games
+% a comment line
+
+[Event "One"]
+[White "Alice"]
+[Black "Bob"]
+
+1. e4 e5 {a comment with a fake result 1-0 and a [bracket}
+2. Nf3 (2. Nc3 {1-0}) Nc6 1/2-1/2 junk after the result
+
+some junk between games
+
+[Event "Two"]
+[White "Carol"
+[Black "Alice"]
+
+1. d4 d5 0-1
+
+[Event "Three"]
+[White "Bob"]
+[Black "Alice"]
+
+1. c4 ; 1-0 in a line comment
+e5 2. Nc3 Nf6 3. Nf3
+[Event "Four"]
+[White "Alice"]
+
+1. e4 e5 2. Ke2 Ke7 3. Kxe5 *
+"#;
+
+#[test]
+fn read_database() {
+    let entries: Vec<Result<GameEntry, PgnError>> = PgnReader::new(DATABASE.as_bytes()).collect();
+
+    assert_eq!(entries.len(), 4);
+
+    let first = entries[0].as_ref().unwrap();
+    assert_eq!(first.line, 4);
+    assert_eq!(first.header("Event"), Some("One"));
+
+    let game = first.game().unwrap();
+    assert_eq!(game.result, GameResult::Draw);
+    assert_eq!(game.moves.moves.len(), 4);
+
+    match &entries[1] {
+        Err(PgnError::Syntax { line, .. }) => assert_eq!(*line, 14),
+        other => panic!("{:?}", other),
+    }
+
+    let third = entries[2].as_ref().unwrap();
+    assert_eq!(third.header("Event"), Some("Three"));
+    assert_eq!(third.game().unwrap().moves.moves.len(), 5);
+    assert_eq!(third.game().unwrap().result, GameResult::Unknown);
+
+    let fourth = entries[3].as_ref().unwrap();
+    assert_eq!(fourth.line, 25);
+
+    let game = fourth.game().unwrap();
+    let mut chess = Chess::new();
+    assert_eq!(
+        game.replay(&mut chess),
+        Err(PgnError::IllegalMove {
+            ply: 5,
+            san: "Kxe5".to_string(),
+            error: MoveError::IllegalMove,
+        })
+    );
+}
+
+#[test]
+fn movetext_errors_report_the_line_in_the_file() {
+    let pgn = "[Event \"x\"]\n\n1. e4 e5\n2. Nf3 )\n*\n";
+
+    let entry = PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap();
+
+    match entry.game() {
+        Err(PgnError::Syntax { line, .. }) => assert_eq!(line, 4),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn filter_database_on_headers() {
+    // games with invalid tag pairs are reported whatever the filter
+    let events: Vec<Option<String>> = PgnReader::new(DATABASE.as_bytes())
+        .filter_header("White", |white| white == "Alice")
+        .map(|entry| entry.ok().map(|e| e.header("Event").unwrap().to_string()))
+        .collect();
+
+    assert_eq!(
+        events,
+        [Some("One".to_string()), None, Some("Four".to_string())]
+    );
+
+    let events: Vec<String> = PgnReader::new(DATABASE.as_bytes())
+        .filter_header("Black", |black| black == "Alice")
+        .filter_header("White", |white| white.starts_with('B'))
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.header("Event").unwrap().to_string())
+        .collect();
+
+    assert_eq!(events, ["Three"]);
+}
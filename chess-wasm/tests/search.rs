@@ -0,0 +1,168 @@
+use chess_wasm::pgn::DEFAULT_POSITION;
+use chess_wasm::search::*;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+mod utils;
+use utils::chess;
+
+#[test]
+fn mate_in_one() {
+    let mut chess = chess("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    let result = search(&mut chess, SearchLimits::depth(4));
+
+    assert_eq!(result.best_move.unwrap().to_uci(), "a1a8");
+    assert_eq!(result.score, MATE - 1);
+    assert_eq!(result.mate_in(), Some(1));
+    assert_eq!(result.pv.len(), 1);
+
+    // there is no quicker mate, so the search stops early
+    assert_eq!(result.depth, 1);
+}
+
+#[test]
+fn mate_in_two() {
+    let fen = "7k/8/8/8/8/8/R7/1R4K1 w - - 0 1";
+    let mut chess = chess(fen);
+    let result = search(&mut chess, SearchLimits::depth(5));
+
+    assert_eq!(result.mate_in(), Some(2));
+    assert_eq!(result.pv.len(), 3);
+    assert_eq!(chess.get_fen(), fen);
+
+    for _move in result.pv {
+        chess.make_move(_move).unwrap();
+    }
+
+    assert!(chess.is_checkmate());
+}
+
+#[test]
+fn getting_mated() {
+    // Kh7 is the only move, then Qg7#
+    let mut chess = chess("7k/8/5K2/8/8/8/8/6Q1 b - - 0 1");
+    let result = search(&mut chess, SearchLimits::depth(2));
+
+    assert_eq!(result.mate_in(), Some(-1));
+    assert_eq!(result.score, -MATE + 2);
+}
+
+#[test]
+fn no_legal_moves() {
+    let mut chess = chess("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+    let result = search(&mut chess, SearchLimits::depth(3));
+
+    assert_eq!(result.best_move, None);
+    assert_eq!(result.score, 0);
+    assert!(result.pv.is_empty());
+}
+
+#[test]
+fn captures() {
+    let mut chess = chess("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1");
+    let result = search(&mut chess, SearchLimits::depth(1));
+
+    assert_eq!(result.best_move.unwrap().to_uci(), "d1d5");
+    assert!(result.score > 400, "{}", result.score);
+}
+
+#[test]
+fn quiescence_sees_the_recapture() {
+    // the pawn on d6 is defended, taking it loses the queen
+    let mut chess = chess("4k3/2p5/3p4/8/8/8/8/3QK3 w - - 0 1");
+    let result = search(&mut chess, SearchLimits::depth(1));
+
+    assert_ne!(result.best_move.unwrap().to_uci(), "d1d6");
+    assert!(result.score > 0, "{}", result.score);
+}
+
+#[test]
+fn repetition_is_a_draw() {
+    // black is about to be mated but can check forever, Qh4+ Kg1 Qe1+ Kh2 Qh4+
+    let mut chess = chess("6k1/RR6/8/8/3q4/8/6P1/7K b - - 0 1");
+    let result = search(&mut chess, SearchLimits::depth(4));
+
+    assert_eq!(result.best_move.unwrap().to_uci(), "d4h4");
+    assert_eq!(result.score, 0);
+}
+
+#[test]
+fn node_limit() {
+    let mut chess = chess(DEFAULT_POSITION);
+    let result = search(&mut chess, SearchLimits::nodes(2000));
+
+    assert!(result.best_move.is_some());
+    assert!(result.depth >= 1);
+    assert!(result.nodes <= 2000, "{}", result.nodes);
+}
+
+#[test]
+fn movetime_limit() {
+    let mut chess = chess(DEFAULT_POSITION);
+    let result = search(
+        &mut chess,
+        SearchLimits::movetime(Duration::from_millis(100)),
+    );
+
+    assert!(result.best_move.is_some());
+    assert_eq!(result.pv.first(), result.best_move.as_ref());
+}
+
+#[test]
+fn first_iteration_is_always_completed() {
+    let mut chess = chess(DEFAULT_POSITION);
+    let result = search(&mut chess, SearchLimits::nodes(1));
+
+    assert_eq!(result.depth, 1);
+    assert!(result.best_move.is_some());
+}
+
+#[test]
+fn stop_flag() {
+    let mut chess = chess(DEFAULT_POSITION);
+    let mut search = Search::new(SearchLimits::default());
+
+    // set before the search, it still completes the first iteration
+    search.stop_flag().store(true, Ordering::SeqCst);
+    let result = search.run(&mut chess);
+
+    assert_eq!(result.depth, 1);
+    assert!(result.best_move.is_some());
+}
+
+#[test]
+fn multi_pv() {
+    // a1a8 mates, the other moves don't
+    let mut chess = chess("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    let mut search = Search::new(SearchLimits::depth(3));
+    search.set_multi_pv(3);
+
+    let mut lines = vec![];
+    let result = search.run_with(&mut chess, |line| lines.push(line.clone()));
+
+    assert_eq!(result.best_move.unwrap().to_uci(), "a1a8");
+    assert_eq!(result.multipv, 1);
+
+    let last: Vec<&SearchResult> = lines
+        .iter()
+        .filter(|line| line.depth == result.depth)
+        .collect();
+    assert_eq!(last.len(), 3);
+    assert_eq!(last[0].pv, result.pv);
+    assert!(last[1].score <= last[0].score && last[2].score <= last[1].score);
+    assert!(last[1].mate_in().is_none());
+    assert_ne!(last[1].best_move, last[2].best_move);
+    assert_eq!(
+        last.iter().map(|line| line.multipv).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+}
+
+#[test]
+fn mate_scores() {
+    assert_eq!(mate_in(MATE - 1), Some(1));
+    assert_eq!(mate_in(MATE - 3), Some(2));
+    assert_eq!(mate_in(-MATE + 2), Some(-1));
+    assert_eq!(mate_in(-MATE + 4), Some(-2));
+    assert_eq!(mate_in(900), None);
+}
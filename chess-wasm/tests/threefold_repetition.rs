@@ -1,4 +1,5 @@
 use chess_wasm::chess::*;
+use chess_wasm::pgn::DEFAULT_POSITION;
 
 #[test]
 fn is_three_fold_repetition() {
@@ -41,3 +42,77 @@ fn is_three_fold_repetition_2() {
     assert_eq!(chess.move_piece("e4"), Ok("e4".to_string()));
     assert!(!chess.is_threefold_repetition());
 }
+
+#[test]
+fn undo_forgets_repetitions() {
+    let mut chess = Chess::new();
+    chess.load_fen(DEFAULT_POSITION.to_string());
+
+    for _move in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"] {
+        chess.move_piece(_move).unwrap();
+    }
+
+    assert_eq!(chess.repetition_count(), 3);
+
+    chess.undo();
+    assert_eq!(chess.repetition_count(), 2);
+    assert!(!chess.is_threefold_repetition());
+
+    chess.move_piece("Ng8").unwrap();
+    assert_eq!(chess.repetition_count(), 3);
+    assert!(chess.is_threefold_repetition());
+}
+
+#[test]
+fn pawn_moves_reset_repetitions() {
+    let mut chess = Chess::new();
+    chess.load_fen(DEFAULT_POSITION.to_string());
+
+    for _move in [
+        "Nf3", "Nf6", "Ng1", "Ng8", "e4", "e5", "Nf3", "Nf6", "Ng1", "Ng8",
+    ] {
+        chess.move_piece(_move).unwrap();
+    }
+
+    assert_eq!(chess.repetition_count(), 2);
+}
+
+#[test]
+fn is_fivefold_repetition() {
+    let mut chess = Chess::new();
+    chess.load_fen(DEFAULT_POSITION.to_string());
+
+    for _ in 0..3 {
+        for _move in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+            chess.move_piece(_move).unwrap();
+        }
+    }
+
+    assert_eq!(chess.repetition_count(), 4);
+    assert!(!chess.is_fivefold_repetition());
+
+    for _move in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+        chess.move_piece(_move).unwrap();
+    }
+
+    assert_eq!(chess.repetition_count(), 5);
+    assert!(chess.is_fivefold_repetition());
+}
+
+#[test]
+fn can_claim_draw() {
+    let mut chess = Chess::new();
+    chess.load_fen(DEFAULT_POSITION.to_string());
+
+    for _move in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1"] {
+        chess.move_piece(_move).unwrap();
+        assert!(!chess.can_claim_draw());
+    }
+
+    chess.move_piece("Ng8").unwrap();
+    assert!(chess.can_claim_draw());
+
+    chess.load_fen("8/8/4k3/8/8/3QK3/8/8 w - - 100 80".to_string());
+    assert!(chess.can_claim_draw());
+    assert!(!chess.is_threefold_repetition());
+}
@@ -0,0 +1,141 @@
+use chess_wasm::pgn::DEFAULT_POSITION;
+use chess_wasm::search::*;
+use chess_wasm::transposition::{self, *};
+
+mod utils;
+use utils::chess;
+
+#[test]
+fn store_and_probe() {
+    let mut table: TranspositionTable<u64> = TranspositionTable::new(1);
+
+    assert_eq!(table.probe(42), None);
+
+    table.store(42, 3, 1000);
+    assert_eq!(
+        table.probe(42),
+        Some(Entry {
+            depth: 3,
+            value: 1000
+        })
+    );
+
+    // the same position is always replaced
+    table.store(42, 1, 7);
+    assert_eq!(table.probe(42), Some(Entry { depth: 1, value: 7 }));
+
+    table.clear();
+    assert_eq!(table.probe(42), None);
+}
+
+#[test]
+fn size() {
+    for mb in [0, 1, 3, 16] {
+        let table: TranspositionTable = TranspositionTable::new(mb);
+
+        assert!(table.capacity().is_power_of_two(), "{}", mb);
+        assert!(table.capacity() >= 4);
+    }
+
+    // 3 MB fits the buckets of 2 MB, but not of 4 MB
+    let two: TranspositionTable = TranspositionTable::new(2);
+    let three: TranspositionTable = TranspositionTable::new(3);
+    assert_eq!(two.capacity(), three.capacity());
+
+    let mut table: TranspositionTable = TranspositionTable::new(1);
+    table.resize(4);
+    assert_eq!(table.capacity(), 2 * three.capacity());
+}
+
+#[test]
+fn replacement() {
+    // a single bucket of 4 entries
+    let mut table: TranspositionTable<u64> = TranspositionTable::new(0);
+    assert_eq!(table.capacity(), 4);
+
+    for key in 1..=4 {
+        table.store(key, key as u8 + 1, key);
+    }
+
+    // the shallowest entry makes room
+    table.store(5, 4, 5);
+    assert_eq!(table.probe(1), None);
+    assert!((2..=5).all(|key| table.probe(key).is_some()));
+
+    // then the entries of previous searches, however deep
+    table.new_search();
+    table.store(6, 1, 6);
+    table.store(7, 1, 7);
+    assert_eq!(table.probe(2), None);
+    assert_eq!(table.probe(5), None);
+    assert!([3, 4, 6, 7].iter().all(|&key| table.probe(key).is_some()));
+}
+
+#[test]
+fn mate_scores_are_stored_from_the_position() {
+    // mate in 5 plies from the root, found 2 plies deep
+    let entry = SearchEntry::new(Bound::Exact, MATE - 5, 2, None);
+
+    assert_eq!(entry.score(2), MATE - 5);
+    assert_eq!(entry.score(4), MATE - 7);
+    assert_eq!(entry.score(0), MATE - 3);
+
+    let entry = SearchEntry::new(Bound::Upper, -MATE + 6, 3, None);
+    assert_eq!(entry.score(1), -MATE + 4);
+
+    let entry = SearchEntry::new(Bound::Lower, 150, 3, None);
+    assert_eq!(entry.score(10), 150);
+}
+
+#[test]
+fn hashfull() {
+    let mut table: TranspositionTable<u64> = TranspositionTable::new(1);
+    assert_eq!(table.hashfull(), 0);
+
+    for key in 0..table.capacity() as u64 {
+        table.store(key, 1, key);
+    }
+
+    assert_eq!(table.hashfull(), 1000);
+
+    // the entries of the previous search don't count
+    table.new_search();
+    assert_eq!(table.hashfull(), 0);
+}
+
+#[test]
+fn cached_perft() {
+    let mut table = TranspositionTable::new(4);
+    let mut start = chess(DEFAULT_POSITION);
+
+    assert_eq!(transposition::perft(&mut start, 4, &mut table), 197281);
+    assert_eq!(transposition::perft(&mut start, 4, &mut table), 197281);
+    assert_eq!(start.get_fen(), DEFAULT_POSITION);
+
+    let mut kiwipete =
+        chess("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    assert_eq!(transposition::perft(&mut kiwipete, 3, &mut table), 97862);
+}
+
+#[test]
+fn search_keeps_the_table() {
+    let mut chess = chess("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let mut search = Search::with_table(SearchLimits::depth(3), TranspositionTable::new(4));
+
+    let first = search.run(&mut chess);
+    let entry = search.table().probe(chess.hash()).unwrap();
+
+    assert_eq!(entry.depth, 3);
+    assert_eq!(entry.value.best_move, first.best_move);
+
+    // the positions of the first search aren't searched again
+    let second = search.run(&mut chess);
+
+    assert_eq!(second.best_move, first.best_move);
+    assert!(
+        second.nodes < first.nodes / 2,
+        "{} {}",
+        second.nodes,
+        first.nodes
+    );
+}
@@ -0,0 +1,81 @@
+use chess_wasm::chess::Chess;
+use chess_wasm::chess2::{Color, Piece, Role, Square};
+use chess_wasm::errors::ParseError;
+
+#[test]
+fn squares() {
+    assert_eq!("e4".parse(), Ok(Square::E4));
+    assert_eq!(Square::E4.to_string(), "e4");
+    assert_eq!((Square::E4.file(), Square::E4.rank()), (4, 3));
+    assert_eq!(Square::from_coords(7, 7), Some(Square::H8));
+    assert_eq!(Square::from_coords(8, 0), None);
+    assert_eq!(Square::new(63), Some(Square::H8));
+    assert_eq!(Square::new(64), None);
+
+    for invalid in ["e9", "i1", "E4", "e", "e44", ""] {
+        assert_eq!(
+            invalid.parse::<Square>(),
+            Err(ParseError::InvalidSquare(invalid.to_string()))
+        );
+    }
+
+    let all: Vec<Square> = Square::all().collect();
+    assert_eq!(all.len(), 64);
+    assert_eq!((all[0], all[63]), (Square::A1, Square::H8));
+
+    for square in all {
+        assert_eq!(square.to_string().parse(), Ok(square));
+    }
+}
+
+#[test]
+fn square_distance() {
+    assert_eq!(Square::E4.distance(Square::E4), 0);
+    assert_eq!(Square::E4.distance(Square::F5), 1);
+    assert_eq!(Square::A1.distance(Square::H8), 7);
+    assert_eq!(Square::B1.distance(Square::C7), 6);
+}
+
+#[test]
+fn colors_roles_and_pieces() {
+    assert_eq!("w".parse(), Ok(Color::White));
+    assert_eq!("black".parse(), Ok(Color::Black));
+    assert_eq!(Color::Black.to_string(), "b");
+    assert!("red".parse::<Color>().is_err());
+
+    assert_eq!("N".parse(), Ok(Role::Knight));
+    assert_eq!(Role::Queen.to_string(), "q");
+    assert!("x".parse::<Role>().is_err());
+
+    let black_knight = Piece::new(Color::Black, Role::Knight);
+
+    assert_eq!("n".parse(), Ok(black_knight));
+    assert_eq!(black_knight.to_string(), "n");
+    assert_eq!(Piece::new(Color::White, Role::King).to_string(), "K");
+    assert_eq!(
+        "Qq".parse::<Piece>(),
+        Err(ParseError::InvalidPiece("Qq".to_string()))
+    );
+}
+
+#[test]
+fn typed_turn_and_moves() {
+    let mut chess = Chess::new();
+    chess.load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
+
+    assert_eq!(chess.turn(), Color::White);
+    assert!(chess.moves(Square::E7).is_empty());
+    assert!(chess.moves(Square::E4).is_empty());
+
+    let _move = chess.parse_san("Nf3").unwrap();
+
+    assert_eq!(_move.from_square(), Square::G1);
+    assert_eq!(_move.to_square(), Square::F3);
+    assert_eq!(_move.moved_piece(), Piece::new(Color::White, Role::Knight));
+    assert_eq!(_move.captured_piece(), None);
+    assert_eq!(_move.promotion_role(), None);
+
+    chess.set_turn(Color::Black);
+    assert_eq!(chess.turn(), Color::Black);
+    assert_eq!(chess.moves(Square::E7), ["e6", "e5"]);
+}
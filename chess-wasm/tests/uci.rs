@@ -0,0 +1,206 @@
+use chess_wasm::chess::Chess;
+use chess_wasm::pgn::DEFAULT_POSITION;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Pipe the commands to the engine and return what it printed
+fn uci(commands: &str) -> String {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    engine
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(commands.as_bytes())
+        .unwrap();
+
+    let output = engine.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn best_moves(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("bestmove "))
+        .map(|line| line.split(' ').next().unwrap())
+        .collect()
+}
+
+#[test]
+fn handshake() {
+    let output = uci("uci\nisready\n");
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert!(lines[0].starts_with("id name chess-wasm"), "{}", output);
+    assert!(lines.contains(&"option name Hash type spin default 16 min 1 max 1024"));
+    assert!(lines.contains(&"option name MultiPV type spin default 1 min 1 max 64"));
+    assert!(lines.contains(&"option name Threads type spin default 1 min 1 max 1"));
+    assert_eq!(lines[lines.len() - 2..], ["uciok", "readyok"]);
+}
+
+#[test]
+fn mate_in_one() {
+    let output = uci("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 3\n");
+
+    let info = output.lines().next().unwrap();
+    assert!(
+        info.starts_with("info depth 1 multipv 1 score mate 1 nodes "),
+        "{}",
+        info
+    );
+    assert!(
+        info.contains(" nps ") && info.ends_with(" pv a1a8"),
+        "{}",
+        info
+    );
+    assert_eq!(best_moves(&output), ["a1a8"]);
+}
+
+#[test]
+fn moves_are_played() {
+    let output = uci("position startpos moves e2e4 e7e5 g1f3\ngo depth 2\n");
+    let best = best_moves(&output)[0];
+
+    let mut chess = Chess::new();
+    chess.load_fen(DEFAULT_POSITION.to_string());
+    for uci in ["e2e4", "e7e5", "g1f3", best].iter() {
+        chess.make_uci_move(uci).unwrap();
+    }
+
+    // black's move, with white's reply to ponder on
+    assert!(
+        output.contains(&format!("bestmove {} ponder ", best)),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn no_legal_moves() {
+    let output = uci("position fen R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1\ngo depth 3\n");
+
+    assert!(output.contains("score mate 0"), "{}", output);
+    assert_eq!(best_moves(&output), ["0000"]);
+}
+
+#[test]
+fn multi_pv() {
+    let output = uci("setoption name MultiPV value 3\nposition startpos\ngo depth 2\n");
+
+    let firsts: Vec<&str> = output
+        .lines()
+        .filter(|line| line.starts_with("info depth 2 "))
+        .map(|line| {
+            line.split(" pv ")
+                .nth(1)
+                .unwrap()
+                .split(' ')
+                .next()
+                .unwrap()
+        })
+        .collect();
+
+    assert_eq!(firsts.len(), 3, "{}", output);
+    assert!(firsts[0] != firsts[1] && firsts[1] != firsts[2] && firsts[0] != firsts[2]);
+    assert!(output.contains("info depth 2 multipv 3 "));
+}
+
+#[test]
+fn limits() {
+    let output = uci("position startpos\ngo nodes 500\n");
+    assert_eq!(best_moves(&output).len(), 1, "{}", output);
+
+    let start = Instant::now();
+    let output = uci("position startpos\ngo movetime 200\n");
+    assert_eq!(best_moves(&output).len(), 1, "{}", output);
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // a 30th of black's time
+    let start = Instant::now();
+    let output = uci("position startpos moves e2e4\ngo wtime 100 btime 3000 winc 0 binc 0\n");
+    assert_eq!(best_moves(&output).len(), 1, "{}", output);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn infinite() {
+    // readyok comes while the search is running, the best move only after stop
+    let output = uci("position startpos\ngo infinite\nisready\nstop\nisready\n");
+    let lines: Vec<&str> = output
+        .lines()
+        .filter(|line| !line.starts_with("info"))
+        .collect();
+
+    assert_eq!(lines[0], "readyok");
+    assert!(lines[1].starts_with("bestmove "), "{}", output);
+    assert_eq!(lines[2], "readyok");
+
+    // at the end of the input, like a bare go
+    let output = uci("position startpos\ngo\n");
+    assert_eq!(best_moves(&output).len(), 1, "{}", output);
+
+    // even with nothing to search
+    let output = uci("position fen R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1\ngo infinite\nstop\n");
+    assert_eq!(best_moves(&output), ["0000"]);
+}
+
+#[test]
+fn quit_stops_the_search() {
+    let output = uci("position startpos\ngo depth 60\nquit\nisready\n");
+
+    assert_eq!(best_moves(&output).len(), 1);
+    assert!(!output.contains("readyok"));
+}
+
+#[test]
+fn errors() {
+    let output = uci("position fen 8/8/8 w - - 0 1\n\
+         position startpos moves e2e4 e7e5 e1e3\n\
+         setoption name Hash value lots\n\
+         setoption name Contempt value 10\n\
+         flip\n\
+         go depth 1\n");
+
+    let errors: Vec<&str> = output
+        .lines()
+        .filter(|line| line.starts_with("info string "))
+        .collect();
+    assert_eq!(errors.len(), 5, "{}", output);
+    assert!(errors[1].contains("e1e3"), "{}", errors[1]);
+
+    // the moves before the illegal one are played
+    let best = best_moves(&output)[0];
+    let mut chess = Chess::new();
+    chess.load_fen(DEFAULT_POSITION.to_string());
+    for uci in ["e2e4", "e7e5", best].iter() {
+        chess.make_uci_move(uci).unwrap();
+    }
+}
+
+#[test]
+fn chess960() {
+    // the king takes its own rook to castle
+    let output = uci("setoption name UCI_Chess960 value true\n\
+         position fen 1r2k2r/8/8/8/8/8/8/1R2K2R w KQkq - 0 1 moves e1h1\n\
+         isready\n");
+
+    assert_eq!(output, "readyok\n");
+}
+
+#[test]
+fn scripted_session() {
+    let script = std::fs::read_to_string("tests/utils/session.uci").unwrap();
+    let output = uci(&script);
+
+    assert_eq!(best_moves(&output).len(), 2, "{}", output);
+    assert_eq!(output.matches("readyok").count(), 2);
+    assert!(output.contains(" multipv 2 "));
+    assert!(!output.contains("info string"), "{}", output);
+}
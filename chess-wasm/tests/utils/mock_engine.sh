@@ -0,0 +1,44 @@
+#!/bin/sh
+# A UCI engine with canned `go perft` answers for the perft tests. It wrongly thinks the black
+# king can jump from a8 to a6 after 1. Kb2 in "k7/8/8/8/8/8/8/K7 w - - 0 1".
+
+position=""
+
+perft() {
+    case "$1|$2" in
+        "position fen k7/8/8/8/8/8/8/K7 w - - 0 1|1")
+            printf 'a1a2: 1\na1b1: 1\na1b2: 1\n\nNodes searched: 3\n\n'
+            ;;
+        "position fen k7/8/8/8/8/8/8/K7 w - - 0 1|2")
+            printf 'a1a2: 3\na1b1: 3\na1b2: 4\n\nNodes searched: 10\n\n'
+            ;;
+        "position fen k7/8/8/8/8/8/8/K7 w - - 0 1 moves a1b2|1")
+            printf 'a8a6: 1\na8a7: 1\na8b7: 1\na8b8: 1\n\nNodes searched: 4\n\n'
+            ;;
+        *)
+            printf 'Nodes searched: 0\n\n'
+            ;;
+    esac
+}
+
+while read -r line; do
+    case "$line" in
+        uci)
+            echo "id name Mock"
+            echo "uciok"
+            ;;
+        isready)
+            echo "readyok"
+            ;;
+        position*)
+            position="$line"
+            ;;
+        "go perft "*)
+            echo "info string perft from a canned answer"
+            perft "$position" "${line#go perft }"
+            ;;
+        quit)
+            exit 0
+            ;;
+    esac
+done
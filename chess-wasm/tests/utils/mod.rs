@@ -1,3 +1,7 @@
+// each test file only uses some of the helpers
+#![allow(dead_code)]
+
+use chess_wasm::chess::Chess;
 use std::{collections::HashSet, hash::Hash};
 
 pub fn compare_vec<T>(a: &[T], b: &[T]) -> bool
@@ -9,3 +13,24 @@ where
 
     a == b
 }
+
+/// A game started from `fen`
+pub fn chess(fen: &str) -> Chess {
+    let mut chess = Chess::new();
+    chess.load_fen(fen.to_string());
+
+    chess
+}
+
+/// A game started from `fen` with the moves in SAN played
+pub fn play(fen: &str, moves: &[&str]) -> Chess {
+    let mut chess = chess(fen);
+
+    for san in moves {
+        if let Err(err) = chess.move_piece(san) {
+            panic!("{}: {}", san, err);
+        }
+    }
+
+    chess
+}
@@ -3,6 +3,8 @@
 #![cfg(target_arch = "wasm32")]
 
 extern crate wasm_bindgen_test;
+use chess_wasm::wasm::Game;
+use wasm_bindgen::JsCast;
 use wasm_bindgen_test::*;
 
 wasm_bindgen_test_configure!(run_in_browser);
@@ -11,3 +13,23 @@ wasm_bindgen_test_configure!(run_in_browser);
 fn pass() {
     assert_eq!(1 + 1, 2);
 }
+
+fn error_name(error: wasm_bindgen::JsValue) -> String {
+    error.unchecked_into::<js_sys::Error>().name().into()
+}
+
+#[wasm_bindgen_test]
+fn game_errors_are_thrown() {
+    let mut game = Game::new(None).unwrap();
+
+    assert_eq!(error_name(game.play("Ke2").unwrap_err()), "MoveError");
+    assert_eq!(error_name(game.play("e2e5").unwrap_err()), "MoveError");
+    assert!(game.legal_moves(Some("i9".to_string())).is_err());
+    assert_eq!(
+        error_name(game.load_fen("not a fen").unwrap_err()),
+        "FenError"
+    );
+    assert!(Game::new(Some("8/8/8/8/8/8/8/8 w - - 0 1".to_string())).is_err());
+
+    assert_eq!(game.fen(), chess_wasm::pgn::DEFAULT_POSITION);
+}